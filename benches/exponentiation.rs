@@ -0,0 +1,83 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use zkp_common::chaum_pedersen;
+use zkp_common::consts::PARAMS;
+use zkp_common::group::{Group, Precomputed};
+use zkp_utils::random;
+
+// What `verify` computed before multi-exponentiation: four independent `modpow`s
+fn verify_naive<G: Group>(
+    group: &G,
+    (y1, y2): (&G::Element, &G::Element),
+    (r1, r2): (&G::Element, &G::Element),
+    c: &G::Scalar,
+    s: &G::Scalar,
+) -> bool {
+    let (g, h) = group.generators();
+    let v1 = group.mul(&group.exp(g, s), &group.exp(y1, c));
+    let v2 = group.mul(&group.exp(h, s), &group.exp(y2, c));
+    r1 == &v1 && r2 == &v2
+}
+
+fn bench_group<G: Group + Clone>(criterion: &mut Criterion, name: &str, group: G) {
+    let precomputed = Precomputed::new(group.clone());
+
+    let x = group.random_scalar(&mut random::rng());
+    let k = group.random_scalar(&mut random::rng());
+    let (y1, y2) = chaum_pedersen::obfuscate(&group, &x);
+    let (r1, r2) = chaum_pedersen::obfuscate(&group, &k);
+
+    let mut bench = criterion.benchmark_group(name);
+    bench.sample_size(20);
+
+    bench.bench_function("obfuscate/naive", |b| {
+        b.iter(|| chaum_pedersen::obfuscate(&group, &x))
+    });
+    bench.bench_function("obfuscate/precomputed", |b| {
+        b.iter(|| chaum_pedersen::obfuscate(&precomputed, &x))
+    });
+
+    // Interactive challenges are drawn from the whole scalar field, Fiat-Shamir ones are 512-bit hashes
+    let challenges = [
+        ("interactive", group.random_scalar(&mut random::rng())),
+        (
+            "non-interactive",
+            chaum_pedersen::challenge(&group, (&y1, &y2), (&r1, &r2), "bench", b"bench"),
+        ),
+    ];
+    for (mode, c) in &challenges {
+        let s = chaum_pedersen::solve_challenge(&group, &k, c, &x);
+        let (y, r) = ((&y1, &y2), (&r1, &r2));
+
+        bench.bench_function(format!("verify/{}/naive", mode), |b| {
+            b.iter(|| assert!(verify_naive(&group, y, r, c, &s)))
+        });
+        bench.bench_function(format!("verify/{}/multi-exp", mode), |b| {
+            b.iter(|| assert!(chaum_pedersen::verify(&group, y, r, c, &s)))
+        });
+        bench.bench_function(format!("verify/{}/precomputed", mode), |b| {
+            b.iter(|| assert!(chaum_pedersen::verify(&precomputed, y, r, c, &s)))
+        });
+    }
+
+    bench.bench_function("precompute", |b| b.iter(|| Precomputed::new(group.clone())));
+    bench.finish();
+}
+
+fn modp2048(criterion: &mut Criterion) {
+    bench_group(criterion, "modp2048", PARAMS.clone());
+}
+
+#[cfg(feature = "ristretto")]
+fn ristretto255(criterion: &mut Criterion) {
+    bench_group(
+        criterion,
+        "ristretto255",
+        zkp_common::consts::RISTRETTO255.clone(),
+    );
+}
+
+#[cfg(not(feature = "ristretto"))]
+fn ristretto255(_: &mut Criterion) {}
+
+criterion_group!(benches, modp2048, ristretto255);
+criterion_main!(benches);
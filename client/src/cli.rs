@@ -1,11 +1,19 @@
 use clap::{Parser, Subcommand};
 use url::{ParseError, Url};
 
+use zkp_common::group::GroupId;
+use zkp_common::sigma::ProtocolId;
+
 #[derive(Debug, Parser)]
 #[clap(author, about, version)]
 pub struct Args {
     #[clap(subcommand)]
     pub command: Command,
+
+    /// Draws all randomness from a deterministic RNG seeded with this, so runs can be replayed.
+    /// Only for testing: the seed gives away every nonce, and with them the password
+    #[clap(long, value_name = "SEED", global = true, verbatim_doc_comment)]
+    pub insecure_seed: Option<u64>,
 }
 
 #[derive(Debug, Subcommand)]
@@ -14,6 +22,8 @@ pub enum Command {
     Register(RegisterCommand),
     /// Logs in an existing user
     Login(LoginCommand),
+    /// Proves membership of a ring of registered users, without revealing which one
+    Member(MemberCommand),
 }
 
 #[derive(Debug, Parser)]
@@ -32,6 +42,17 @@ pub struct RegisterCommand {
     )]
     pub password: Option<String>,
 
+    /// Sets the protocol logins will prove knowledge of the password with:
+    /// `chaum-pedersen`, `schnorr` (smaller proofs) or `okamoto` (two secrets)
+    #[clap(
+        short = 'P',
+        long,
+        value_name = "PROTOCOL",
+        default_value = "chaum-pedersen"
+    )]
+    #[clap(verbatim_doc_comment)]
+    pub protocol: ProtocolId,
+
     #[clap(flatten)]
     pub server: ServerOptions,
 }
@@ -52,6 +73,51 @@ pub struct LoginCommand {
     )]
     pub password: Option<String>,
 
+    #[clap(flatten)]
+    pub options: LoginOptions,
+
+    #[clap(flatten)]
+    pub server: ServerOptions,
+}
+
+#[derive(Debug, Parser)]
+pub struct LoginOptions {
+    /// Proves knowledge of the password in a single request (Fiat-Shamir)
+    #[clap(short, long)]
+    pub non_interactive: bool,
+
+    /// Derives the nonce from the password and fresh randomness (RFC 6979 style),
+    /// so a weak random number generator can't leak the password
+    #[clap(short, long)]
+    pub deterministic_nonce: bool,
+}
+
+#[derive(Debug, Parser)]
+pub struct MemberCommand {
+    /// Specifies the username to prove membership with
+    #[clap(short, long, value_name = "USERNAME")]
+    pub username: Option<String>,
+
+    /// Specifies the password to prove membership with
+    #[clap(
+        short,
+        long,
+        value_name = "PASSWORD",
+        env = "PASSWORD",
+        hide_env_values = true
+    )]
+    pub password: Option<String>,
+
+    /// Specifies the users to hide among, yourself included (comma-separated)
+    #[clap(
+        short,
+        long,
+        value_name = "USERS",
+        value_delimiter = ',',
+        required = true
+    )]
+    pub ring: Vec<String>,
+
     #[clap(flatten)]
     pub server: ServerOptions,
 }
@@ -66,9 +132,33 @@ pub struct ServerOptions {
     /// Specifies the server address to connect to
     #[clap(short = 's', long = "server", value_name = "URI", default_value = DEFAULT_ADDR, value_parser = test_validity)]
     pub addr: String,
+
+    /// Requires the server to run the protocol in this group [default: whichever the server reports]
+    #[clap(short, long, value_name = "GROUP")]
+    pub group: Option<GroupId>,
+
+    /// Requires the server's group fingerprint to be this (hex-encoded SHA-256)
+    #[clap(short, long, value_name = "HEX", value_parser = test_fingerprint)]
+    pub fingerprint: Option<String>,
+
+    /// Requires the server to identify itself by this name [default: whichever the server reports]
+    #[clap(long, value_name = "NAME")]
+    pub server_name: Option<String>,
+
+    /// Requires the server to hold accounts in this realm [default: whichever the server reports]
+    #[clap(long, value_name = "REALM")]
+    pub realm: Option<String>,
 }
 
 fn test_validity(val: &str) -> Result<String, ParseError> {
     let _: Url = val.parse()?;
     Ok(val.to_string())
 }
+
+fn test_fingerprint(val: &str) -> Result<String, String> {
+    match hex::decode(val) {
+        Ok(bytes) if bytes.len() == 32 => Ok(val.to_string()),
+        Ok(_) => Err("fingerprint must be 32 bytes".to_string()),
+        Err(err) => Err(err.to_string()),
+    }
+}
@@ -1,17 +1,212 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use clap::Parser;
-use log::error;
+use log::{error, info, warn};
+use tonic::transport::Channel;
 
-use zkp_common::{consts, proto};
-use zkp_utils::{biguint, logger, random, string, style};
+use zkp_common::group::{Group, GroupId, NamedGroup};
+use zkp_common::proof::{Challenge, Message, Statement};
+use zkp_common::secret::{Password, Secret};
+use zkp_common::sigma::compose::OrProof;
+use zkp_common::sigma::{self, ChaumPedersen, Okamoto, ProtocolId, Schnorr, SigmaProtocol};
+use zkp_common::{
+    kdf, login_context, membership_context, nonce, proto, Context, PROTOCOL_VERSION,
+    REGISTRATION_CONTEXT,
+};
+use zkp_utils::random::SecureRng;
+use zkp_utils::{logger, style};
 
 mod cli;
 mod utils;
 
 const MAX_TRIES: usize = 3;
 
-async fn register_user(details: cli::RegisterCommand) -> anyhow::Result<()> {
-    let mut client = proto::AuthClient::connect(details.server.addr).await?;
+type Client = proto::AuthClient<Channel>;
+
+// How each protocol's witness is derived from the password
+trait Credential<G: Group>: SigmaProtocol<G> {
+    fn derive_witness(
+        group: &G,
+        password: &str,
+        salt: &[u8],
+    ) -> Result<Secret<Self::Witness>, kdf::Error>;
+}
+
+impl<G: Group> Credential<G> for ChaumPedersen {
+    fn derive_witness(
+        group: &G,
+        password: &str,
+        salt: &[u8],
+    ) -> Result<Secret<G::Scalar>, kdf::Error> {
+        kdf::derive_secret(group, password, salt)
+    }
+}
+
+impl<G: Group> Credential<G> for Schnorr {
+    fn derive_witness(
+        group: &G,
+        password: &str,
+        salt: &[u8],
+    ) -> Result<Secret<G::Scalar>, kdf::Error> {
+        kdf::derive_secret(group, password, salt)
+    }
+}
+
+impl<G: Group> Credential<G> for Okamoto {
+    fn derive_witness(
+        group: &G,
+        password: &str,
+        salt: &[u8],
+    ) -> Result<Secret<[G::Scalar; 2]>, kdf::Error> {
+        kdf::derive_secrets(group, password, salt)
+    }
+}
+
+// The statement to register for the password in protocol `P`, and the proof that goes with it
+fn registration<G: Group, P: Credential<G>>(
+    group: &G,
+    rng: &mut SecureRng,
+    context: &Context,
+    user_id: &str,
+    password: &Password,
+    salt: &[u8],
+) -> anyhow::Result<(proto::Statement, proto::Proof)> {
+    let x = P::derive_witness(group, password.expose(), salt)?;
+
+    let (prover, _) = sigma::Prover::<G, P>::commit_random(group, rng);
+    let proof = prover.prove(
+        group,
+        x.expose(),
+        user_id,
+        &context.bind(user_id, REGISTRATION_CONTEXT),
+    );
+
+    Ok((
+        P::statement(group, x.expose()).to_proto(group),
+        proof.to_proto(group),
+    ))
+}
+
+// The group the server reports, which must be exactly the standard one it names and match
+// whatever the user required of it
+fn check_group(
+    reported: &proto::ParametersResponse,
+    server: &cli::ServerOptions,
+) -> anyhow::Result<GroupId> {
+    let Ok(group_id) = reported.group.parse::<GroupId>() else {
+        anyhow::bail!(
+            "server uses group '{}', which this client doesn't support",
+            reported.group
+        );
+    };
+
+    if let Some(expected) = server.group {
+        if expected != group_id {
+            anyhow::bail!(
+                "server uses group '{}', but '{}' was requested",
+                group_id,
+                expected
+            );
+        }
+    }
+
+    let named_group = group_id.resolve();
+    named_group.validate()?;
+
+    let parameters: Vec<_> = [&reported.g, &reported.h, &reported.p, &reported.q]
+        .into_iter()
+        .filter(|v| !v.is_empty())
+        .cloned()
+        .collect();
+    if parameters != named_group.parameters() || reported.fingerprint != named_group.fingerprint() {
+        anyhow::bail!(
+            "server's parameters for group '{}' differ from the standard ones",
+            group_id
+        );
+    }
+
+    let fingerprint = hex::encode(&reported.fingerprint);
+    if let Some(pinned) = &server.fingerprint {
+        if !pinned.eq_ignore_ascii_case(&fingerprint) {
+            anyhow::bail!(
+                "server's group fingerprint '{}' does not match the pinned '{}'",
+                fingerprint,
+                pinned
+            );
+        }
+    }
+
+    Ok(group_id)
+}
+
+// Connects to the server and settles on the group it runs the protocol in, and the context
+// every proof is bound to
+async fn connect(server: &cli::ServerOptions) -> anyhow::Result<(Client, GroupId, Context)> {
+    let mut client = proto::AuthClient::connect(server.addr.clone()).await?;
+
+    let reported = client
+        .get_parameters(tonic::Request::new(proto::ParametersRequest {}))
+        .await?
+        .into_inner();
+    let group_id = check_group(&reported, server)?;
+    let proto::ParametersResponse {
+        fingerprint,
+        server: server_name,
+        realm,
+        version,
+        ..
+    } = reported;
+    let fingerprint = hex::encode(fingerprint);
+
+    if version != PROTOCOL_VERSION {
+        anyhow::bail!(
+            "server speaks version {} of the protocol, but this client speaks version {}",
+            version,
+            PROTOCOL_VERSION
+        );
+    }
+    for (what, pinned, reported) in [
+        ("name", &server.server_name, &server_name),
+        ("realm", &server.realm, &realm),
+    ] {
+        if let Some(pinned) = pinned {
+            if pinned != reported {
+                anyhow::bail!(
+                    "server's {} '{}' does not match the pinned '{}'",
+                    what,
+                    reported,
+                    pinned
+                );
+            }
+        }
+    }
+
+    info!(
+        "server '{}{}{}' (realm '{}{}{}') uses group '{}{}{}' with fingerprint '{}{}{}'",
+        style::fg::CYAN,
+        server_name,
+        style::fg::RESET,
+        style::fg::CYAN,
+        realm,
+        style::fg::RESET,
+        style::fg::CYAN,
+        group_id,
+        style::fg::RESET,
+        style::fg::CYAN,
+        fingerprint,
+        style::fg::RESET
+    );
+
+    Ok((client, group_id, Context::new(server_name, realm)))
+}
 
+async fn register_user<G: Group>(
+    group: &G,
+    rng: &mut SecureRng,
+    mut client: Client,
+    context: &Context,
+    details: cli::RegisterCommand,
+) -> anyhow::Result<()> {
     eprintln!("=============== ZKP Auth (Registration) ===============");
     let mut user_id = utils::maybe_input(details.username, "Enter a User ID:")?;
     let mut password = utils::maybe_password(details.password, "Select a Password:")?;
@@ -21,14 +216,26 @@ async fn register_user(details: cli::RegisterCommand) -> anyhow::Result<()> {
                 user_id = utils::maybe_input(None, "Enter a User ID:")?;
                 password = utils::maybe_password(None, "Select a Password:")?;
             }
-            let x = string::as_biguint(&password);
-
-            let (y1, y2) = consts::PARAMS.obfuscate(&x);
+            let salt = rng.bytes(kdf::SALT_LEN);
+            let (statement, proof) = match details.protocol {
+                ProtocolId::ChaumPedersen => registration::<_, ChaumPedersen>(
+                    group, rng, context, &user_id, &password, &salt,
+                )?,
+                ProtocolId::Schnorr => {
+                    registration::<_, Schnorr>(group, rng, context, &user_id, &password, &salt)?
+                }
+                ProtocolId::Okamoto => {
+                    registration::<_, Okamoto>(group, rng, context, &user_id, &password, &salt)?
+                }
+            };
 
             let register_request = tonic::Request::new(proto::RegisterRequest {
                 user: user_id.clone(),
-                y1: biguint::serialize(y1),
-                y2: biguint::serialize(y2),
+                statement: Some(statement),
+                salt,
+                protocol: details.protocol.to_string(),
+                additional: Vec::new(),
+                proof: Some(proof),
             });
             if let Err(err) = client.register(register_request).await {
                 match err.code() {
@@ -68,106 +275,312 @@ async fn register_user(details: cli::RegisterCommand) -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn login_user(details: cli::LoginCommand) -> anyhow::Result<()> {
-    let mut client = proto::AuthClient::connect(details.server.addr).await?;
+// Fetches the user's salt and protocol ahead of a proof, returning `None` (after logging why) if the server has none
+async fn fetch_salt(client: &mut Client, user_id: &str) -> Option<(Vec<u8>, ProtocolId)> {
+    match client
+        .get_salt(tonic::Request::new(proto::SaltRequest {
+            user: user_id.to_string(),
+        }))
+        .await
+    {
+        Ok(salt_response) => {
+            let proto::SaltResponse { salt, protocol, .. } = salt_response.into_inner();
+            match protocol.parse() {
+                Ok(protocol) => Some((salt, protocol)),
+                Err(err) => {
+                    error!(
+                        "user '{}{}{}' is registered with an unsupported protocol: {}",
+                        style::fg::YELLOW,
+                        user_id,
+                        style::fg::RESET,
+                        err
+                    );
+                    None
+                }
+            }
+        }
+        Err(err) => {
+            match err.code() {
+                tonic::Code::NotFound => error!(
+                    "user '{}{}{}' does not exist",
+                    style::fg::YELLOW,
+                    user_id,
+                    style::fg::RESET
+                ),
+                _ => {
+                    error!(
+                        "failed to fetch user salt: '{}{:?}{}'",
+                        style::fg::RED,
+                        err.code(),
+                        style::fg::RESET
+                    );
+                }
+            }
+            None
+        }
+    }
+}
+
+// Proves knowledge of the password in the protocol the user registered with, returning the
+// session ID on success
+async fn login<G: Group>(
+    group: &G,
+    rng: &mut SecureRng,
+    client: &mut Client,
+    context: &Context,
+    user_id: &str,
+    password: &Password,
+    options: &cli::LoginOptions,
+) -> anyhow::Result<Option<String>> {
+    let Some((salt, protocol)) = fetch_salt(client, user_id).await else {
+        return Ok(None);
+    };
 
+    macro_rules! login_as {
+        ($protocol:ty) => {{
+            let x = <$protocol as Credential<G>>::derive_witness(group, password.expose(), &salt)?;
+            if options.non_interactive {
+                non_interactive_login::<G, $protocol>(
+                    group,
+                    rng,
+                    client,
+                    context,
+                    user_id,
+                    &x,
+                    options.deterministic_nonce,
+                )
+                .await
+            } else {
+                interactive_login::<G, $protocol>(
+                    group,
+                    rng,
+                    client,
+                    user_id,
+                    &x,
+                    options.deterministic_nonce,
+                )
+                .await
+            }
+        }};
+    }
+
+    match protocol {
+        ProtocolId::ChaumPedersen => login_as!(ChaumPedersen),
+        ProtocolId::Schnorr => login_as!(Schnorr),
+        ProtocolId::Okamoto => login_as!(Okamoto),
+    }
+}
+
+// Runs the three-step (commit, challenge, answer) login, returning the session ID on success
+async fn interactive_login<G: Group, P: SigmaProtocol<G>>(
+    group: &G,
+    rng: &mut SecureRng,
+    client: &mut Client,
+    user_id: &str,
+    x: &Secret<P::Witness>,
+    deterministic_nonce: bool,
+) -> anyhow::Result<Option<String>> {
+    // k: random k, or derived from x. The challenge only arrives after committing to k,
+    // so the context is the current time to keep it unique.
+    let k = if deterministic_nonce {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+        nonce::derive_for::<G, P, _>(group, x.expose(), user_id, &now.to_be_bytes(), rng)
+    } else {
+        Secret::new(P::random_nonce(group, rng))
+    };
+
+    let (prover, commitment) = sigma::Prover::<G, P>::commit(group, k);
+
+    let auth_response = match client
+        .create_authentication_challenge(tonic::Request::new(
+            proto::AuthenticationChallengeRequest {
+                user: user_id.to_string(),
+                commitment: Some(commitment.to_proto(group)),
+            },
+        ))
+        .await
+    {
+        Ok(auth_response) => auth_response,
+        Err(err) => {
+            match err.code() {
+                tonic::Code::NotFound => error!(
+                    "user '{}{}{}' does not exist",
+                    style::fg::YELLOW,
+                    user_id,
+                    style::fg::RESET
+                ),
+                _ => {
+                    error!(
+                        "failed to create authentication challenge: '{}{:?}{}'",
+                        style::fg::RED,
+                        err.code(),
+                        style::fg::RESET
+                    );
+                }
+            }
+            return Ok(None);
+        }
+    };
+
+    let proto::AuthenticationChallengeResponse {
+        auth_id, challenge, ..
+    } = auth_response.into_inner();
+
+    let Some(Ok(challenge)) = challenge.map(|challenge| Challenge::try_from((group, challenge)))
+    else {
+        error!("server sent a malformed challenge");
+        return Ok(None);
+    };
+    let response = prover.respond(group, &challenge, x.expose());
+
+    match client
+        .verify_authentication(tonic::Request::new(proto::AuthenticationAnswerRequest {
+            auth_id,
+            response: Some(response.to_proto(group)),
+        }))
+        .await
+    {
+        Ok(auth_ans_response) => {
+            let proto::AuthenticationAnswerResponse { session_id } = auth_ans_response.into_inner();
+
+            Ok(Some(session_id))
+        }
+        Err(err) => {
+            match err.code() {
+                tonic::Code::NotFound => error!(
+                    "user '{}{}{}' does not have an authentication challenge",
+                    style::fg::YELLOW,
+                    user_id,
+                    style::fg::RESET
+                ),
+                tonic::Code::Unauthenticated => {
+                    error!("failed to authenticate, invalid credentials",)
+                }
+                tonic::Code::DeadlineExceeded => {
+                    error!("failed to authenticate, the challenge expired before it was answered")
+                }
+                _ => {
+                    error!(
+                        "failed to verify authentication: '{}{:?}{}'",
+                        style::fg::RED,
+                        err.code(),
+                        style::fg::RESET
+                    );
+                }
+            }
+            Ok(None)
+        }
+    }
+}
+
+// Sends a single self-challenged (Fiat-Shamir) proof, returning the session ID on success
+async fn non_interactive_login<G: Group, P: SigmaProtocol<G>>(
+    group: &G,
+    rng: &mut SecureRng,
+    client: &mut Client,
+    context: &Context,
+    user_id: &str,
+    x: &Secret<P::Witness>,
+    deterministic_nonce: bool,
+) -> anyhow::Result<Option<String>> {
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let context = context.bind(user_id, &login_context(timestamp));
+
+    // k: random k, or derived from x and the proof's context
+    let k = if deterministic_nonce {
+        nonce::derive_for::<G, P, _>(group, x.expose(), user_id, &context, rng)
+    } else {
+        Secret::new(P::random_nonce(group, rng))
+    };
+
+    let (prover, _) = sigma::Prover::<G, P>::commit(group, k);
+    let proof = prover.prove(group, x.expose(), user_id, &context);
+
+    match client
+        .verify_non_interactive_authentication(tonic::Request::new(
+            proto::NonInteractiveAuthenticationRequest {
+                user: user_id.to_string(),
+                proof: Some((group, &proof).into()),
+                timestamp,
+            },
+        ))
+        .await
+    {
+        Ok(auth_ans_response) => {
+            let proto::AuthenticationAnswerResponse { session_id } = auth_ans_response.into_inner();
+
+            Ok(Some(session_id))
+        }
+        Err(err) => {
+            match err.code() {
+                tonic::Code::NotFound => error!(
+                    "user '{}{}{}' does not exist",
+                    style::fg::YELLOW,
+                    user_id,
+                    style::fg::RESET
+                ),
+                tonic::Code::Unauthenticated => {
+                    error!("failed to authenticate, invalid credentials",)
+                }
+                tonic::Code::DeadlineExceeded => {
+                    error!("failed to authenticate, local clock is out of sync with the server")
+                }
+                _ => {
+                    error!(
+                        "failed to verify authentication: '{}{:?}{}'",
+                        style::fg::RED,
+                        err.code(),
+                        style::fg::RESET
+                    );
+                }
+            }
+            Ok(None)
+        }
+    }
+}
+
+async fn login_user<G: Group>(
+    group: &G,
+    rng: &mut SecureRng,
+    mut client: Client,
+    context: &Context,
+    details: cli::LoginCommand,
+) -> anyhow::Result<()> {
     eprintln!("=================== ZKP Auth (Login) ==================");
-    let mut user_id = utils::maybe_input(details.username, "Enter Your User ID:")?;
-    let mut password = utils::maybe_password(details.password, "Enter Your Password:")?;
+    let cli::LoginCommand {
+        username,
+        password,
+        options,
+        ..
+    } = details;
+    let mut user_id = utils::maybe_input(username, "Enter Your User ID:")?;
+    let mut password = utils::maybe_password(password, "Enter Your Password:")?;
     'outer: {
         for i in 0..MAX_TRIES {
-            'inner: {
-                if i > 0 {
-                    user_id = utils::maybe_input(None, "Enter Your User ID:")?;
-                    password = utils::maybe_password(None, "Enter Your Password:")?;
-                }
-                let x = string::as_biguint(&password);
-
-                // k: random k
-                let k = random::biguint(&consts::PARAMS.Q);
-
-                let (r1, r2) = consts::PARAMS.obfuscate(&k);
-
-                let auth_response = match client
-                    .create_authentication_challenge(tonic::Request::new(
-                        proto::AuthenticationChallengeRequest {
-                            user: user_id.clone(),
-                            r1: biguint::serialize(r1),
-                            r2: biguint::serialize(r2),
-                        },
-                    ))
-                    .await
-                {
-                    Ok(auth_response) => auth_response,
-                    Err(err) => {
-                        match err.code() {
-                            tonic::Code::NotFound => error!(
-                                "user '{}{}{}' does not exist",
-                                style::fg::YELLOW,
-                                user_id,
-                                style::fg::RESET
-                            ),
-                            _ => {
-                                error!(
-                                    "failed to create authentication challenge: '{}{:?}{}'",
-                                    style::fg::RED,
-                                    err.code(),
-                                    style::fg::RESET
-                                );
-                            }
-                        }
-                        break 'inner;
-                    }
-                };
-
-                let proto::AuthenticationChallengeResponse { auth_id, c } =
-                    auth_response.into_inner();
-
-                let c = biguint::deserialize(&c);
-
-                let s = consts::PARAMS.solve_challenge(&k, &c, &x);
-
-                match client
-                    .verify_authentication(tonic::Request::new(
-                        proto::AuthenticationAnswerRequest {
-                            auth_id,
-                            s: biguint::serialize(s),
-                        },
-                    ))
-                    .await
-                {
-                    Ok(auth_ans_response) => {
-                        let proto::AuthenticationAnswerResponse { session_id } =
-                            auth_ans_response.into_inner();
-
-                        println!(
-                            "{}[i]{} Successfully authenticated user, session ID is: {:?}",
-                            style::fg::GREEN,
-                            style::fg::RESET,
-                            session_id
-                        );
-                        break 'outer;
-                    }
-                    Err(err) => match err.code() {
-                        tonic::Code::NotFound => error!(
-                            "user '{}{}{}' does not have an authentication challenge",
-                            style::fg::YELLOW,
-                            user_id,
-                            style::fg::RESET
-                        ),
-                        tonic::Code::Unauthenticated => {
-                            error!("failed to authenticate, invalid credentials",)
-                        }
-                        _ => {
-                            error!(
-                                "failed to verify authentication: '{}{:?}{}'",
-                                style::fg::RED,
-                                err.code(),
-                                style::fg::RESET
-                            );
-                        }
-                    },
-                }
+            if i > 0 {
+                user_id = utils::maybe_input(None, "Enter Your User ID:")?;
+                password = utils::maybe_password(None, "Enter Your Password:")?;
+            }
+
+            let session_id = login(
+                group,
+                rng,
+                &mut client,
+                context,
+                &user_id,
+                &password,
+                &options,
+            )
+            .await?;
+
+            if let Some(session_id) = session_id {
+                println!(
+                    "{}[i]{} Successfully authenticated user, session ID is: {:?}",
+                    style::fg::GREEN,
+                    style::fg::RESET,
+                    session_id
+                );
+                break 'outer;
             }
 
             eprintln!(
@@ -183,12 +596,165 @@ async fn login_user(details: cli::LoginCommand) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn prove_membership<G: Group>(
+    group: &G,
+    rng: &mut SecureRng,
+    mut client: Client,
+    context: &Context,
+    details: cli::MemberCommand,
+) -> anyhow::Result<()> {
+    eprintln!("================= ZKP Auth (Membership) ================");
+    let user_id = utils::maybe_input(details.username, "Enter Your User ID:")?;
+    let password = utils::maybe_password(details.password, "Enter Your Password:")?;
+
+    let Some(index) = details.ring.iter().position(|member| *member == user_id) else {
+        anyhow::bail!("user '{}' must be one of the ring's members", user_id);
+    };
+
+    let Some((salt, protocol)) = fetch_salt(&mut client, &user_id).await else {
+        anyhow::bail!("failed to fetch the salt of user '{}'", user_id);
+    };
+    if protocol != ProtocolId::ChaumPedersen {
+        anyhow::bail!(
+            "user '{}' is registered with '{}', but membership proofs need '{}'",
+            user_id,
+            protocol,
+            ProtocolId::ChaumPedersen
+        );
+    }
+    let x = kdf::derive_secret(group, password.expose(), &salt)?;
+
+    // Only logged-in users may fetch statements
+    let Some(session_id) = non_interactive_login::<G, ChaumPedersen>(
+        group,
+        rng,
+        &mut client,
+        context,
+        &user_id,
+        &x,
+        false,
+    )
+    .await?
+    else {
+        anyhow::bail!("failed to log in as user '{}'", user_id);
+    };
+
+    let proto::MembersResponse { statements } = client
+        .get_members(tonic::Request::new(proto::MembersRequest {
+            users: details.ring.clone(),
+            user: user_id.clone(),
+            session_id,
+        }))
+        .await?
+        .into_inner();
+    let ring = statements
+        .into_iter()
+        .map(|statement| Statement::try_from((group, statement)))
+        .collect::<Result<Vec<_>, _>>()?;
+    if ring.len() != details.ring.len() {
+        anyhow::bail!(
+            "server returned {} statements for {} users",
+            ring.len(),
+            details.ring.len()
+        );
+    }
+
+    let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let proof = OrProof::membership(
+        group,
+        &ring,
+        index,
+        x.expose(),
+        &context.bind("", &membership_context(timestamp)),
+        rng,
+    );
+
+    match client
+        .verify_membership(tonic::Request::new(proto::MembershipRequest {
+            ring: details.ring,
+            proof: Some(proof.to_proto(group)),
+            timestamp,
+        }))
+        .await
+    {
+        Ok(membership_response) => {
+            let proto::MembershipResponse { token } = membership_response.into_inner();
+            println!(
+                "{}[i]{} Successfully proved membership, anonymous session token is: {:?}",
+                style::fg::GREEN,
+                style::fg::RESET,
+                token
+            );
+        }
+        Err(err) => match err.code() {
+            tonic::Code::Unauthenticated => {
+                error!("failed to prove membership, invalid credentials")
+            }
+            tonic::Code::DeadlineExceeded => {
+                error!("failed to prove membership, local clock is out of sync with the server")
+            }
+            _ => {
+                error!(
+                    "failed to verify membership: '{}{:?}{}'",
+                    style::fg::RED,
+                    err.code(),
+                    style::fg::RESET
+                );
+            }
+        },
+    }
+    eprintln!("================= ZKP Auth (Membership) ================");
+
+    Ok(())
+}
+
 async fn init() -> anyhow::Result<()> {
     let args = cli::Args::parse();
 
+    let mut rng = match args.insecure_seed {
+        Some(seed) => {
+            warn!("drawing secrets from a seeded RNG, anyone who knows the seed can recover them");
+            SecureRng::seeded(seed)
+        }
+        None => SecureRng::os(),
+    };
+    let rng = &mut rng;
+
     match args.command {
-        cli::Command::Register(register) => register_user(register).await?,
-        cli::Command::Login(login) => login_user(login).await?,
+        cli::Command::Register(register) => {
+            let (client, group_id, context) = connect(&register.server).await?;
+            match group_id.resolve() {
+                NamedGroup::Modp(group) => {
+                    register_user(group, rng, client, &context, register).await?
+                }
+                #[cfg(feature = "ristretto")]
+                NamedGroup::Ristretto255(group) => {
+                    register_user(group, rng, client, &context, register).await?
+                }
+            }
+        }
+        cli::Command::Login(login) => {
+            let (client, group_id, context) = connect(&login.server).await?;
+            match group_id.resolve() {
+                NamedGroup::Modp(group) => login_user(group, rng, client, &context, login).await?,
+                #[cfg(feature = "ristretto")]
+                NamedGroup::Ristretto255(group) => {
+                    login_user(group, rng, client, &context, login).await?
+                }
+            }
+        }
+        cli::Command::Member(member) => {
+            let (client, group_id, context) = connect(&member.server).await?;
+            match group_id.resolve() {
+                NamedGroup::Modp(group) => {
+                    prove_membership(group, rng, client, &context, member).await?
+                }
+                #[cfg(feature = "ristretto")]
+                NamedGroup::Ristretto255(group) => {
+                    prove_membership(group, rng, client, &context, member).await?
+                }
+            }
+        }
     }
 
     Ok(())
@@ -202,3 +768,84 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options() -> cli::ServerOptions {
+        cli::ServerOptions {
+            addr: "http://127.0.0.1:3000".to_string(),
+            group: None,
+            fingerprint: None,
+            server_name: None,
+            realm: None,
+        }
+    }
+
+    // What an honest server running `group_id` reports
+    fn reported(group_id: GroupId) -> proto::ParametersResponse {
+        let group = group_id.resolve();
+        let mut parameters = group.parameters().into_iter();
+        let mut next = || parameters.next().unwrap_or_default();
+
+        proto::ParametersResponse {
+            group: group_id.to_string(),
+            g: next(),
+            h: next(),
+            p: next(),
+            q: next(),
+            fingerprint: group.fingerprint().to_vec(),
+            server: "zkp-auth".to_string(),
+            realm: "default".to_string(),
+            version: PROTOCOL_VERSION,
+        }
+    }
+
+    #[test]
+    fn group_negotiation() {
+        let honest = reported(GroupId::Modp2048);
+        assert_eq!(check_group(&honest, &options()).unwrap(), GroupId::Modp2048);
+
+        // A group this client doesn't know
+        let unknown = proto::ParametersResponse {
+            group: "modp1024".to_string(),
+            ..honest.clone()
+        };
+        assert!(check_group(&unknown, &options()).is_err());
+
+        // Another group than the one asked for
+        let requested = cli::ServerOptions {
+            group: Some(GroupId::Ffdhe2048),
+            ..options()
+        };
+        assert!(check_group(&honest, &requested).is_err());
+        assert_eq!(
+            check_group(&reported(GroupId::Ffdhe2048), &requested).unwrap(),
+            GroupId::Ffdhe2048
+        );
+
+        // Tampered parameters, with or without a matching fingerprint
+        let mut tampered = honest.clone();
+        tampered.h = reported(GroupId::Ffdhe2048).h;
+        assert!(check_group(&tampered, &options()).is_err());
+        let mut tampered = honest.clone();
+        tampered.fingerprint[0] ^= 1;
+        assert!(check_group(&tampered, &options()).is_err());
+
+        // Pinned fingerprints are hex, whatever the case
+        let fingerprint = hex::encode(&honest.fingerprint);
+        for pinned in [fingerprint.to_lowercase(), fingerprint.to_uppercase()] {
+            let pinned = cli::ServerOptions {
+                fingerprint: Some(pinned),
+                ..options()
+            };
+            assert_eq!(check_group(&honest, &pinned).unwrap(), GroupId::Modp2048);
+        }
+        let other = cli::ServerOptions {
+            fingerprint: Some(hex::encode(reported(GroupId::Ffdhe2048).fingerprint)),
+            ..options()
+        };
+        assert!(check_group(&honest, &other).is_err());
+    }
+}
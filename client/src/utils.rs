@@ -1,5 +1,6 @@
 use std::io::{self, Write};
 
+use zkp_common::secret::Password;
 use zkp_utils::style;
 
 pub fn maybe_input(value: Option<String>, prompt: &str) -> anyhow::Result<String> {
@@ -23,9 +24,9 @@ pub fn maybe_input(value: Option<String>, prompt: &str) -> anyhow::Result<String
     }
 }
 
-pub fn maybe_password(value: Option<String>, prompt: &str) -> anyhow::Result<String> {
+pub fn maybe_password(value: Option<String>, prompt: &str) -> anyhow::Result<Password> {
     match value {
-        Some(val) => Ok(val),
+        Some(val) => Ok(Password::new(val)),
         None => {
             print!(
                 "{}{}{} {} ",
@@ -35,7 +36,7 @@ pub fn maybe_password(value: Option<String>, prompt: &str) -> anyhow::Result<Str
                 prompt
             );
             io::stdout().flush()?;
-            Ok(rpassword::read_password()?)
+            Ok(Password::new(rpassword::read_password()?))
         }
     }
 }
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use log::{debug, error};
+use tokio::sync::{mpsc, oneshot};
+
+use zkp_common::group::Group;
+use zkp_common::proof::{self, Transcript};
+use zkp_common::protocol::VerifyError;
+use zkp_utils::random::SecureRng;
+
+// The most transcripts checked together: past this, a batch holding one bad transcript spends
+// more time isolating it than batching saved
+const MAX_BATCH: usize = 64;
+
+type Pending<G> = (Transcript<G>, oneshot::Sender<Result<(), VerifyError>>);
+
+/// Checks transcripts on a background task, together with whichever others arrived while the
+/// previous batch was being checked
+///
+/// Under light load every batch holds a single transcript and this costs the same as checking
+/// it directly; as answers pile up, each batch costs little more than its largest transcript.
+pub struct BatchVerifier<G: Group> {
+    queue: mpsc::UnboundedSender<Pending<G>>,
+}
+
+impl<G: Group> BatchVerifier<G> {
+    /// Spawn the background task onto the current runtime
+    pub fn spawn(group: Arc<G>) -> Self {
+        let (queue, pending) = mpsc::unbounded_channel();
+        tokio::spawn(run(group, pending));
+
+        Self { queue }
+    }
+
+    pub async fn verify(&self, transcript: Transcript<G>) -> Result<(), VerifyError> {
+        let (reply, result) = oneshot::channel();
+        // Both ends only close if the task died, and then nothing can be verified
+        if self.queue.send((transcript, reply)).is_err() {
+            error!("batch verifier is gone, rejecting proof");
+            return Err(VerifyError::Rejected);
+        }
+
+        result.await.unwrap_or(Err(VerifyError::Rejected))
+    }
+}
+
+async fn run<G: Group>(group: Arc<G>, mut pending: mpsc::UnboundedReceiver<Pending<G>>) {
+    // The weights only need to be unpredictable to provers, and come from their own RNG so a
+    // seeded server still hands out the same challenges and session IDs
+    let mut rng = SecureRng::os();
+
+    while let Some(first) = pending.recv().await {
+        let mut batch = vec![first];
+        while batch.len() < MAX_BATCH {
+            match pending.try_recv() {
+                Ok(next) => batch.push(next),
+                Err(_) => break,
+            }
+        }
+        let (transcripts, replies): (Vec<_>, Vec<_>) = batch.into_iter().unzip();
+        debug!("verifying a batch of {} proofs", transcripts.len());
+
+        let group = group.clone();
+        let checked = tokio::task::spawn_blocking(move || {
+            let result = proof::verify_batch(&*group, &transcripts, &mut rng);
+            (result, rng)
+        })
+        .await;
+
+        // Dropping the replies on a panic rejects every proof in the batch
+        let Ok((result, returned)) = checked else {
+            error!("batch verification panicked");
+            rng = SecureRng::os();
+            continue;
+        };
+        rng = returned;
+
+        let failed = result.err().unwrap_or_default();
+        for (index, reply) in replies.into_iter().enumerate() {
+            let outcome = if failed.binary_search(&index).is_ok() {
+                Err(VerifyError::Rejected)
+            } else {
+                Ok(())
+            };
+            // The handler may have been cancelled meanwhile, which is fine
+            let _ = reply.send(outcome);
+        }
+    }
+}
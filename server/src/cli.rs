@@ -4,11 +4,17 @@ use std::net::{AddrParseError, IpAddr, Ipv4Addr, SocketAddr};
 use clap::Parser;
 use log::warn;
 
+use zkp_common::group::GroupId;
 use zkp_utils::style;
 
+use crate::store::StoreKind;
+
 pub const DEFAULT_PORT: u16 = 3000;
 pub const DEFAULT_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), DEFAULT_PORT);
 
+// Far longer than any login takes, and it keeps expiry times well clear of overflowing
+const MAX_CHALLENGE_TTL: u64 = 24 * 60 * 60;
+
 #[derive(Debug, Parser)]
 #[clap(author, about, version)]
 pub struct Args {
@@ -18,6 +24,34 @@ pub struct Args {
     #[clap(verbatim_doc_comment, value_parser = addr_from_str)]
     #[clap(default_value = "127.0.0.1", hide_default_value = true)]
     pub listen: SocketAddr,
+
+    /// Sets the group the protocol runs in
+    #[clap(short, long, value_name = "GROUP", default_value = "modp2048")]
+    pub group: GroupId,
+
+    /// Sets the name the server identifies itself by, which every proof is bound to
+    #[clap(short, long, value_name = "NAME", default_value = "zkp-auth")]
+    pub name: String,
+
+    /// Sets the realm of accounts, which every proof is bound to
+    #[clap(short, long, value_name = "REALM", default_value = "default")]
+    pub realm: String,
+
+    /// Sets where users, challenges and sessions are kept:
+    /// `memory` (lost on restart), `sqlite:<PATH>` or `redb:<PATH>` (created if missing)
+    #[clap(short, long, value_name = "STORE", default_value = "memory")]
+    #[clap(verbatim_doc_comment)]
+    pub store: StoreKind,
+
+    /// Sets how long (in seconds, at most a day) an interactive login has to answer its challenge
+    #[clap(long, value_name = "SECONDS", default_value = "60")]
+    #[clap(value_parser = clap::value_parser!(u64).range(1..=MAX_CHALLENGE_TTL))]
+    pub challenge_ttl: u64,
+
+    /// Draws all randomness from a deterministic RNG seeded with this, so runs can be replayed.
+    /// Only for testing: the seed gives away every challenge and session ID
+    #[clap(long, value_name = "SEED", verbatim_doc_comment)]
+    pub insecure_seed: Option<u64>,
 }
 
 pub fn addr_from_str(s: &str) -> Result<SocketAddr, AddrParseError> {
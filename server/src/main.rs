@@ -1,48 +1,54 @@
-use std::collections::HashMap;
+// Handlers and their helpers all return `tonic::Status`, which is unavoidably large
+#![allow(clippy::result_large_err)]
+
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use clap::Parser;
-use log::{debug, error, info};
-use num_bigint::BigUint;
-use tokio::sync::RwLock;
+use log::{debug, error, info, warn};
+use subtle::ConstantTimeEq;
+use tokio::sync::{Mutex, RwLock};
 use tonic::{async_trait, transport::Server};
 
-use zkp_common::{consts, proto};
-use zkp_utils::{biguint, logger, random, style};
+use zkp_common::group::{Group, GroupId, NamedGroup, Precomputed};
+use zkp_common::proof::{self, Challenge, DecodeError, Message, Proof, Statement, Transcript};
+use zkp_common::protocol::{Verifier, VerifyError};
+use zkp_common::sigma::compose::{AndProof, OrProof};
+use zkp_common::sigma::{self, ChaumPedersen, Okamoto, ProtocolId, Schnorr};
+use zkp_common::{
+    kdf, login_context, membership_context, proto, Context, LOGIN_CONTEXT, REGISTRATION_CONTEXT,
+};
+use zkp_utils::random::SecureRng;
+use zkp_utils::{logger, style};
 
+mod batch;
 mod cli;
+mod store;
 
-#[derive(Debug)]
-pub struct UserData {
-    pub state: State,
-    pub credentials: Credentials,
-}
-
-#[derive(Debug)]
-pub struct Credentials {
-    pub y1: BigUint,
-    pub y2: BigUint,
-}
+use store::{
+    Challenged, Credentials, Issued, Registered, StoreError, StoreKind, UserData, UserName,
+    UserStore,
+};
 
-#[derive(Debug)]
-pub enum State {
-    Authenticated {
-        session_id: String,
-    },
-    Authenticating {
-        r1: BigUint,
-        r2: BigUint,
-        c: BigUint,
-    },
-    Unauthenticated,
+// Chaum-Pedersen proofs wait to be checked in a batch, the others are checked as soon as they're decoded
+enum Check<G: Group> {
+    Batched(Transcript<G>),
+    Checked(Result<(), VerifyError>),
 }
 
-type UserName = String;
-type AuthId = String;
+type CommitmentId = Vec<u8>;
 
-#[derive(Default)]
-pub struct AuthService {
-    pub user_datastore: RwLock<HashMap<UserName, UserData>>,
-    pub auth_pairs: RwLock<HashMap<AuthId, UserName>>, // improvement: these auth pair entries should expire after some time
+pub struct AuthService<G: Group> {
+    pub group_id: GroupId,
+    pub context: Context, // every challenge is bound to this deployment
+    pub group: Arc<G>,
+    pub store: Arc<dyn UserStore<G>>, // credentials, in-flight challenges and sessions
+    pub challenge_ttl: u64, // how long (in seconds) an interactive login has to answer its challenge
+    pub used_commitments: RwLock<HashMap<CommitmentId, u64>>, // non-interactive r1 values seen within the validity window
+    pub rng: Mutex<SecureRng>, // challenges, auth ids and session ids are all drawn from here
+    pub verifier: batch::BatchVerifier<G>, // answers arriving together are checked together
 }
 
 // Alphanumeric Permutations: (26 + 10) ^ 32 = 63340286662973277706162286946811886609896461828096
@@ -52,8 +58,360 @@ const AUTH_ID_LEN: usize = 32;
 // User-scoped, so this is fine
 const SESSION_ID_LEN: usize = 12;
 
+// Alphanumeric Permutations: (26 + 10) ^ 32, as many as auth ids
+// Not user-scoped, so as long as an auth id
+const TOKEN_LEN: usize = 32;
+
+// The fewest users a membership proof may hide among
+const MIN_RING: usize = 2;
+
+// The most, since verifying costs a store lookup and an OR branch per member
+const MAX_RING: usize = 64;
+
+// How far (in seconds) a non-interactive proof's timestamp may drift from the server clock
+const PROOF_VALIDITY: u64 = 60;
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn store_failed(err: StoreError) -> tonic::Status {
+    error!("{}", err);
+
+    tonic::Status::internal("failed to reach the user store")
+}
+
+fn replayed(user: &str) -> tonic::Status {
+    error!(
+        "non-interactive proof for user '{}{}{}' was replayed",
+        style::fg::CYAN,
+        user,
+        style::fg::RESET
+    );
+
+    tonic::Status::unauthenticated("proof has already been used")
+}
+
+// The r1 values of a composed proof's branches, which together identify it for replay protection
+fn composed_commitment<G: Group>(group: &G, commitments: &[proof::Commitment<G>]) -> CommitmentId {
+    commitments
+        .iter()
+        .flat_map(|commitment| group.encode_element(&commitment.r1))
+        .collect()
+}
+
+impl<G: Group> AuthService<G> {
+    // Spawns the batch verifier and the challenge reaper, so this must run inside the runtime
+    pub fn new(
+        group_id: GroupId,
+        group: Arc<G>,
+        store: Arc<dyn UserStore<G>>,
+        challenge_ttl: u64,
+        context: Context,
+        rng: SecureRng,
+    ) -> Self {
+        // Sweeping as often as challenges expire keeps stale ones around for three TTLs at most
+        tokio::spawn(store::reap_challenges(
+            Arc::downgrade(&store),
+            Duration::from_secs(challenge_ttl),
+        ));
+
+        Self {
+            group_id,
+            context,
+            verifier: batch::BatchVerifier::spawn(group.clone()),
+            group,
+            store,
+            challenge_ttl,
+            used_commitments: RwLock::default(),
+            rng: Mutex::new(rng),
+        }
+    }
+
+    // The user's credentials, which must be registered
+    async fn user(&self, user: &str) -> Result<UserData<G>, tonic::Status> {
+        match self.store.user(user).await.map_err(store_failed)? {
+            Some(user_data) => Ok(user_data),
+            None => {
+                error!(
+                    "user '{}{}{}' not found",
+                    style::fg::CYAN,
+                    user,
+                    style::fg::RESET
+                );
+
+                Err(tonic::Status::not_found(format!(
+                    "user '{}' not found",
+                    user
+                )))
+            }
+        }
+    }
+
+    // Checks that `session_id` is the session `user` last started
+    async fn authenticated(&self, user: &str, session_id: &str) -> Result<(), tonic::Status> {
+        let session = self.store.session(user).await.map_err(store_failed)?;
+        let matches = session
+            .is_some_and(|session| bool::from(session.as_bytes().ct_eq(session_id.as_bytes())));
+        if !matches {
+            error!(
+                "user '{}{}{}' has no such session",
+                style::fg::CYAN,
+                user,
+                style::fg::RESET
+            );
+
+            return Err(tonic::Status::unauthenticated("invalid session"));
+        }
+
+        Ok(())
+    }
+
+    // Decodes one of the request's proof objects, which must be present and canonically encoded
+    fn decode<M, T>(&self, field: &'static str, message: Option<M>) -> Result<T, tonic::Status>
+    where
+        T: for<'g> TryFrom<(&'g G, M), Error = DecodeError>,
+    {
+        message
+            .ok_or(DecodeError::Missing(field))
+            .and_then(|message| T::try_from((&*self.group, message)))
+            .map_err(|err| {
+                error!(
+                    "received a malformed '{}{}{}': {}",
+                    style::fg::YELLOW,
+                    field,
+                    style::fg::RESET,
+                    err
+                );
+
+                tonic::Status::invalid_argument(err.to_string())
+            })
+    }
+
+    fn check_salt(&self, user: &str, salt: &[u8]) -> Result<(), tonic::Status> {
+        if salt.len() == kdf::SALT_LEN {
+            return Ok(());
+        }
+
+        error!(
+            "user '{}{}{}' supplied a {}-byte salt",
+            style::fg::CYAN,
+            user,
+            style::fg::RESET,
+            salt.len()
+        );
+
+        Err(tonic::Status::invalid_argument(format!(
+            "salt must be {} bytes",
+            kdf::SALT_LEN
+        )))
+    }
+
+    // Decodes a statement in protocol `P` along with the registrant's proof that they know its
+    // secret, which keeps out statements nobody could ever log in with (such as Chaum-Pedersen
+    // ones whose halves have different exponents)
+    fn registered<P: sigma::SigmaProtocol<G>>(
+        &self,
+        user: &str,
+        statement: Option<proto::Statement>,
+        proof: Option<proto::Proof>,
+    ) -> Result<P::Statement, tonic::Status> {
+        let statement: P::Statement = self.decode("statement", statement)?;
+        let proof: Proof<G, P> = self.decode("proof", proof)?;
+
+        if let Err(err) = sigma::Verifier::<G, P>::new(statement.clone()).verify_proof(
+            &self.group,
+            &proof,
+            user,
+            &self.context.bind(user, REGISTRATION_CONTEXT),
+        ) {
+            error!(
+                "user '{}{}{}' didn't prove knowledge of their statement's secret: {}",
+                style::fg::CYAN,
+                user,
+                style::fg::RESET,
+                err
+            );
+
+            return Err(tonic::Status::invalid_argument("statement proof failed"));
+        }
+
+        Ok(statement)
+    }
+
+    // Rejects a non-interactive proof whose timestamp is outside the validity window,
+    // returning the current time otherwise
+    fn check_timestamp(&self, user: &str, timestamp: u64) -> Result<u64, tonic::Status> {
+        let now = unix_time();
+        if now.abs_diff(timestamp) <= PROOF_VALIDITY {
+            return Ok(now);
+        }
+
+        error!(
+            "non-interactive proof for user '{}{}{}' is stale",
+            style::fg::CYAN,
+            user,
+            style::fg::RESET
+        );
+
+        Err(tonic::Status::deadline_exceeded(
+            "proof timestamp is outside the validity window",
+        ))
+    }
+
+    // Whether a non-interactive proof's commitment was already used within the validity window
+    async fn is_used(&self, now: u64, commitment: &CommitmentId) -> bool {
+        let mut used_commitments = self.used_commitments.write().await;
+        used_commitments.retain(|_, seen| now.abs_diff(*seen) <= PROOF_VALIDITY);
+        used_commitments.contains_key(commitment)
+    }
+
+    // Records a verified proof's commitment, returning `false` if the same proof was verified
+    // concurrently: only the first one counts
+    async fn mark_used(&self, commitment: CommitmentId, timestamp: u64) -> bool {
+        self.used_commitments
+            .write()
+            .await
+            .insert(commitment, timestamp)
+            .is_none()
+    }
+
+    // Rejects a non-interactive proof whose commitment was already used within the validity window
+    async fn check_unused(
+        &self,
+        user: &str,
+        now: u64,
+        commitment: &CommitmentId,
+    ) -> Result<(), tonic::Status> {
+        if self.is_used(now, commitment).await {
+            return Err(replayed(user));
+        }
+
+        Ok(())
+    }
+
+    // Starts a session for a user whose non-interactive proof checked out
+    async fn accept_proof(
+        &self,
+        user: &str,
+        commitment: CommitmentId,
+        timestamp: u64,
+    ) -> Result<tonic::Response<proto::AuthenticationAnswerResponse>, tonic::Status> {
+        if !self.mark_used(commitment, timestamp).await {
+            return Err(replayed(user));
+        }
+
+        let session_id = self.rng.lock().await.alphanumeric(SESSION_ID_LEN);
+        self.store
+            .start_session(user, &session_id)
+            .await
+            .map_err(store_failed)?;
+
+        info!(
+            "user '{}{}{}' authenticated successfully",
+            style::fg::CYAN,
+            user,
+            style::fg::RESET
+        );
+        Ok(tonic::Response::new(proto::AuthenticationAnswerResponse {
+            session_id,
+        }))
+    }
+
+    // The Chaum-Pedersen statements of the users in `ring`, which must all exist, be distinct
+    // and be enough of them that a member can hide among the others, but no more than the
+    // server is willing to look up
+    async fn ring_statements(&self, ring: &[UserName]) -> Result<Vec<Statement<G>>, tonic::Status> {
+        if ring.len() > MAX_RING {
+            error!("ring of {} users is too large", ring.len());
+
+            return Err(tonic::Status::invalid_argument(format!(
+                "ring may hold at most {} users",
+                MAX_RING
+            )));
+        }
+
+        let distinct: HashSet<_> = ring.iter().collect();
+        if ring.len() < MIN_RING || distinct.len() != ring.len() {
+            error!(
+                "ring of {} users is too small or repeats a user",
+                ring.len()
+            );
+
+            return Err(tonic::Status::invalid_argument(format!(
+                "ring must hold at least {} distinct users",
+                MIN_RING
+            )));
+        }
+
+        let mut statements = Vec::with_capacity(ring.len());
+        for user in ring {
+            match self.user(user).await?.credentials.statement {
+                Registered::ChaumPedersen(statement) => statements.push(statement),
+                _ => {
+                    error!(
+                        "user '{}{}{}' can't be a ring member",
+                        style::fg::CYAN,
+                        user,
+                        style::fg::RESET
+                    );
+
+                    return Err(tonic::Status::failed_precondition(format!(
+                        "user '{}' isn't registered with a Chaum-Pedersen statement",
+                        user
+                    )));
+                }
+            }
+        }
+
+        Ok(statements)
+    }
+
+    // Answers a commitment in protocol `P`, the one the user registered with
+    fn challenge<P: sigma::SigmaProtocol<G>>(
+        &self,
+        user: &str,
+        statement: &P::Statement,
+        commitment: Option<proto::Commitment>,
+        rng: &mut SecureRng,
+    ) -> Result<(sigma::ChallengedVerifier<G, P>, Challenge<G>), tonic::Status> {
+        let commitment = self.decode("commitment", commitment)?;
+
+        Ok(sigma::Verifier::new(statement.clone()).challenge(
+            &*self.group,
+            commitment,
+            user,
+            &self.context.bind(user, LOGIN_CONTEXT),
+            rng,
+        ))
+    }
+
+    // Checks a non-interactive proof in protocol `P`, the one the user registered with,
+    // returning the `r1` that identifies it along with the outcome
+    fn check_proof<P: sigma::SigmaProtocol<G>>(
+        &self,
+        statement: &P::Statement,
+        proof: Option<proto::Proof>,
+        user: &str,
+        context: &[u8],
+    ) -> Result<(CommitmentId, Check<G>), tonic::Status> {
+        let proof: Proof<G, P> = self.decode("proof", proof)?;
+        let commitment = proof.commitment.to_proto(&*self.group).r1;
+        let outcome = sigma::Verifier::new(statement.clone()).verify_proof(
+            &*self.group,
+            &proof,
+            user,
+            context,
+        );
+
+        Ok((commitment, Check::Checked(outcome)))
+    }
+}
+
 #[async_trait]
-impl proto::Auth for AuthService {
+impl<G: Group> proto::Auth for AuthService<G> {
     async fn register(
         &self,
         req: tonic::Request<proto::RegisterRequest>,
@@ -65,7 +423,14 @@ impl proto::Auth for AuthService {
             style::fg::RESET,
             req
         );
-        let proto::RegisterRequest { user, y1, y2 } = req.into_inner();
+        let proto::RegisterRequest {
+            user,
+            statement,
+            salt,
+            protocol,
+            additional,
+            proof,
+        } = req.into_inner();
 
         info!(
             "'{}{}{}' received for '{}{}{}'",
@@ -77,12 +442,59 @@ impl proto::Auth for AuthService {
             style::fg::RESET,
         );
 
-        let y1 = biguint::deserialize(&y1);
-        let y2 = biguint::deserialize(&y2);
+        self.check_salt(&user, &salt)?;
 
-        let mut user_datastore = self.user_datastore.write().await;
+        let statement = match protocol.parse::<ProtocolId>() {
+            Ok(ProtocolId::ChaumPedersen) => Registered::ChaumPedersen(
+                self.registered::<ChaumPedersen>(&user, statement, proof)?,
+            ),
+            Ok(ProtocolId::Schnorr) => {
+                Registered::Schnorr(self.registered::<Schnorr>(&user, statement, proof)?)
+            }
+            Ok(ProtocolId::Okamoto) => {
+                Registered::Okamoto(self.registered::<Okamoto>(&user, statement, proof)?)
+            }
+            Err(err) => {
+                error!(
+                    "user '{}{}{}' tried to register with an unknown protocol: {}",
+                    style::fg::CYAN,
+                    user,
+                    style::fg::RESET,
+                    err
+                );
+
+                return Err(tonic::Status::invalid_argument(err.to_string()));
+            }
+        };
 
-        if user_datastore.contains_key(&user) {
+        let additional = additional
+            .into_iter()
+            .map(|credential| {
+                self.check_salt(&user, &credential.salt)?;
+
+                Ok(Credentials {
+                    statement: Registered::ChaumPedersen(self.registered::<ChaumPedersen>(
+                        &user,
+                        credential.statement,
+                        credential.proof,
+                    )?),
+                    salt: credential.salt,
+                })
+            })
+            .collect::<Result<Vec<_>, tonic::Status>>()?;
+
+        let user_details = UserData {
+            credentials: Credentials { statement, salt },
+            additional,
+        };
+
+        let protocol = user_details.credentials.statement.protocol();
+        if !self
+            .store
+            .register(&user, user_details)
+            .await
+            .map_err(store_failed)?
+        {
             error!(
                 "user '{}{}{}' already exists",
                 style::fg::CYAN,
@@ -96,16 +508,13 @@ impl proto::Auth for AuthService {
             )));
         }
 
-        let user_details = UserData {
-            state: State::Unauthenticated,
-            credentials: Credentials { y1, y2 },
-        };
-
-        user_datastore.insert(user.clone(), user_details);
         info!(
-            "user '{}{}{}' registered successfully",
+            "user '{}{}{}' registered successfully with '{}{}{}'",
             style::fg::CYAN,
             user,
+            style::fg::RESET,
+            style::fg::CYAN,
+            protocol,
             style::fg::RESET
         );
 
@@ -123,7 +532,7 @@ impl proto::Auth for AuthService {
             style::fg::RESET,
             req
         );
-        let proto::AuthenticationChallengeRequest { user, r1, r2 } = req.into_inner();
+        let proto::AuthenticationChallengeRequest { user, commitment } = req.into_inner();
 
         info!(
             "'{}{}{}' received for '{}{}{}'",
@@ -135,36 +544,39 @@ impl proto::Auth for AuthService {
             style::fg::RESET,
         );
 
-        let r1 = biguint::deserialize(&r1);
-        let r2 = biguint::deserialize(&r2);
-
-        // c: random c
-        let c = random::biguint(&consts::PARAMS.Q);
-
-        let auth_id = random::alphanumeric(AUTH_ID_LEN);
-
-        let mut user_datastore = self.user_datastore.write().await;
-        let mut auth_pairs = self.auth_pairs.write().await;
-        if let Some(user_data) = user_datastore.get_mut(&user) {
-            user_data.state = State::Authenticating {
-                r1,
-                r2,
-                c: c.clone(),
+        let user_data = self.user(&user).await?;
+        let (verifier, challenge, auth_id) = {
+            let mut rng = self.rng.lock().await;
+            // c: random c
+            let (verifier, challenge) = match &user_data.credentials.statement {
+                Registered::ChaumPedersen(statement) => {
+                    let (verifier, challenge) =
+                        self.challenge::<ChaumPedersen>(&user, statement, commitment, &mut rng)?;
+                    (Challenged::ChaumPedersen(verifier), challenge)
+                }
+                Registered::Schnorr(statement) => {
+                    let (verifier, challenge) =
+                        self.challenge::<Schnorr>(&user, statement, commitment, &mut rng)?;
+                    (Challenged::Schnorr(verifier), challenge)
+                }
+                Registered::Okamoto(statement) => {
+                    let (verifier, challenge) =
+                        self.challenge::<Okamoto>(&user, statement, commitment, &mut rng)?;
+                    (Challenged::Okamoto(verifier), challenge)
+                }
             };
-        } else {
-            error!(
-                "user '{}{}{}' not found",
-                style::fg::CYAN,
-                user,
-                style::fg::RESET
-            );
 
-            return Err(tonic::Status::not_found(format!(
-                "user '{}' not found",
-                user
-            )));
-        }
-        auth_pairs.insert(auth_id.clone(), user.clone());
+            (verifier, challenge, rng.alphanumeric(AUTH_ID_LEN))
+        };
+        let issued = Issued {
+            user: user.clone(),
+            verifier,
+            expires: unix_time().saturating_add(self.challenge_ttl),
+        };
+        self.store
+            .begin_challenge(&auth_id, issued)
+            .await
+            .map_err(store_failed)?;
         info!(
             "authentication challenge created for user '{}{}{}'",
             style::fg::CYAN,
@@ -175,7 +587,8 @@ impl proto::Auth for AuthService {
         Ok(tonic::Response::new(
             proto::AuthenticationChallengeResponse {
                 auth_id,
-                c: biguint::serialize(c),
+                challenge: Some((&*self.group, &challenge).into()),
+                salt: user_data.credentials.salt,
             },
         ))
     }
@@ -191,7 +604,7 @@ impl proto::Auth for AuthService {
             style::fg::RESET,
             req
         );
-        let proto::AuthenticationAnswerRequest { auth_id, s } = req.into_inner();
+        let proto::AuthenticationAnswerRequest { auth_id, response } = req.into_inner();
 
         info!(
             "'{}{}{}' received with auth_id '{}{}{}'",
@@ -203,27 +616,17 @@ impl proto::Auth for AuthService {
             style::fg::RESET,
         );
 
-        let s = biguint::deserialize(&s);
-
-        let mut auth_pairs = self.auth_pairs.write().await;
-        let mut user_datastore = self.user_datastore.write().await;
-
-        let (user_id, user) = if let Some(user_id) = auth_pairs.remove(&auth_id) {
-            if let Some(user_data) = user_datastore.get_mut(&user_id) {
-                (user_id, user_data)
-            } else {
-                error!(
-                    "authentication challenge created for user '{}{}{}' who doesn't exist",
-                    style::fg::CYAN,
-                    user_id,
-                    style::fg::RESET
-                );
-
-                return Err(tonic::Status::internal(
-                    "an authentication challenge was created for a user that doesn't exist",
-                ));
-            }
-        } else {
+        // Whatever the outcome, the challenge is used up
+        let Some(Issued {
+            user: user_id,
+            verifier,
+            expires,
+        }) = self
+            .store
+            .take_challenge(&auth_id)
+            .await
+            .map_err(store_failed)?
+        else {
             error!(
                 "authentication challenge with auth_id '{}{}{}' not found / expired",
                 style::fg::CYAN,
@@ -237,74 +640,485 @@ impl proto::Auth for AuthService {
             )));
         };
 
-        let Credentials { y1, y2 } = &user.credentials;
-
-        let (r1, r2, c) = if let State::Authenticating { r1, r2, c } = &user.state {
-            (r1, r2, c)
-        } else {
+        if unix_time() > expires {
             error!(
-                "user '{}{}{}' is not expecting to be authenticated",
+                "authentication challenge for user '{}{}{}' expired before it was answered",
                 style::fg::CYAN,
                 user_id,
                 style::fg::RESET
             );
 
-            return Err(tonic::Status::internal(
-                "this user is not expecting to be authenticated",
+            return Err(tonic::Status::deadline_exceeded(
+                "authentication challenge expired",
             ));
+        }
+
+        // Chaum-Pedersen answers go through the batch verifier, the others are checked right away
+        let verified = match verifier {
+            Challenged::ChaumPedersen(verifier) => {
+                let response = self.decode("response", response)?;
+                self.verifier.verify(verifier.transcript(response)).await
+            }
+            Challenged::Schnorr(verifier) => {
+                verifier.verify(&self.group, &self.decode("response", response)?)
+            }
+            Challenged::Okamoto(verifier) => {
+                verifier.verify(&self.group, &self.decode("response", response)?)
+            }
         };
 
-        if consts::PARAMS.verify((y1, y2), (r1, r2), c, &s) {
-            let session_id = random::alphanumeric(SESSION_ID_LEN);
-            user.state = State::Authenticated {
-                session_id: session_id.clone(),
-            };
+        match verified {
+            Ok(()) => {
+                let session_id = self.rng.lock().await.alphanumeric(SESSION_ID_LEN);
+                self.store
+                    .start_session(&user_id, &session_id)
+                    .await
+                    .map_err(store_failed)?;
 
-            info!(
-                "user '{}{}{}' authenticated successfully",
-                style::fg::CYAN,
-                user_id,
-                style::fg::RESET
-            );
-            Ok(tonic::Response::new(proto::AuthenticationAnswerResponse {
-                session_id,
-            }))
-        } else {
-            user.state = State::Unauthenticated;
+                info!(
+                    "user '{}{}{}' authenticated successfully",
+                    style::fg::CYAN,
+                    user_id,
+                    style::fg::RESET
+                );
+                Ok(tonic::Response::new(proto::AuthenticationAnswerResponse {
+                    session_id,
+                }))
+            }
+            Err(err) => {
+                error!(
+                    "authentication challenge failed for user '{}{}{}': {}",
+                    style::fg::CYAN,
+                    user_id,
+                    style::fg::RESET,
+                    err
+                );
+                Err(tonic::Status::unauthenticated(
+                    "authentication challenge failed",
+                ))
+            }
+        }
+    }
+
+    async fn get_parameters(
+        &self,
+        req: tonic::Request<proto::ParametersRequest>,
+    ) -> Result<tonic::Response<proto::ParametersResponse>, tonic::Status> {
+        debug!(
+            "'{}{}{}' received: {:?}",
+            style::fg::YELLOW,
+            "ParametersRequest",
+            style::fg::RESET,
+            req
+        );
+
+        let mut parameters = self.group.parameters().into_iter();
+        let mut next = || parameters.next().unwrap_or_default();
 
+        Ok(tonic::Response::new(proto::ParametersResponse {
+            group: self.group_id.to_string(),
+            g: next(),
+            h: next(),
+            p: next(),
+            q: next(),
+            fingerprint: self.group.fingerprint().to_vec(),
+            server: self.context.server.clone(),
+            realm: self.context.realm.clone(),
+            version: self.context.version,
+        }))
+    }
+
+    async fn get_salt(
+        &self,
+        req: tonic::Request<proto::SaltRequest>,
+    ) -> Result<tonic::Response<proto::SaltResponse>, tonic::Status> {
+        debug!(
+            "'{}{}{}' received: {:?}",
+            style::fg::YELLOW,
+            "SaltRequest",
+            style::fg::RESET,
+            req
+        );
+        let proto::SaltRequest { user } = req.into_inner();
+
+        let user_data = self.user(&user).await?;
+
+        Ok(tonic::Response::new(proto::SaltResponse {
+            salt: user_data.credentials.salt,
+            protocol: user_data.credentials.statement.protocol().to_string(),
+            additional: user_data
+                .additional
+                .into_iter()
+                .map(|credentials| credentials.salt)
+                .collect(),
+        }))
+    }
+
+    async fn verify_non_interactive_authentication(
+        &self,
+        req: tonic::Request<proto::NonInteractiveAuthenticationRequest>,
+    ) -> Result<tonic::Response<proto::AuthenticationAnswerResponse>, tonic::Status> {
+        debug!(
+            "'{}{}{}' received: {:?}",
+            style::fg::YELLOW,
+            "NonInteractiveAuthenticationRequest",
+            style::fg::RESET,
+            req
+        );
+        let proto::NonInteractiveAuthenticationRequest {
+            user,
+            proof,
+            timestamp,
+        } = req.into_inner();
+
+        info!(
+            "'{}{}{}' received for '{}{}{}'",
+            style::fg::YELLOW,
+            "NonInteractiveAuthenticationRequest",
+            style::fg::RESET,
+            style::fg::CYAN,
+            user,
+            style::fg::RESET,
+        );
+
+        let now = self.check_timestamp(&user, timestamp)?;
+
+        let user_data = self.user(&user).await?;
+
+        let context = self.context.bind(&user, &login_context(timestamp));
+        let (commitment, check) = match &user_data.credentials.statement {
+            Registered::ChaumPedersen(statement) => {
+                let proof: Proof<G> = self.decode("proof", proof)?;
+                // r1 alone pins down the nonce, so it identifies the proof for replay protection
+                let commitment = self.group.encode_element(&proof.commitment.r1);
+                let transcript = Verifier::new(statement.clone()).proof_transcript(
+                    &self.group,
+                    proof,
+                    &user,
+                    &context,
+                );
+                (commitment, Check::Batched(transcript))
+            }
+            Registered::Schnorr(statement) => {
+                self.check_proof::<Schnorr>(statement, proof, &user, &context)?
+            }
+            Registered::Okamoto(statement) => {
+                self.check_proof::<Okamoto>(statement, proof, &user, &context)?
+            }
+        };
+        self.check_unused(&user, now, &commitment).await?;
+
+        let verified = match check {
+            Check::Batched(transcript) => self.verifier.verify(transcript).await,
+            Check::Checked(outcome) => outcome,
+        };
+
+        match verified {
+            Ok(()) => self.accept_proof(&user, commitment, timestamp).await,
+            Err(err) => {
+                error!(
+                    "non-interactive proof failed for user '{}{}{}': {}",
+                    style::fg::CYAN,
+                    user,
+                    style::fg::RESET,
+                    err
+                );
+                Err(tonic::Status::unauthenticated(
+                    "authentication proof failed",
+                ))
+            }
+        }
+    }
+
+    async fn verify_composed_authentication(
+        &self,
+        req: tonic::Request<proto::ComposedAuthenticationRequest>,
+    ) -> Result<tonic::Response<proto::AuthenticationAnswerResponse>, tonic::Status> {
+        debug!(
+            "'{}{}{}' received: {:?}",
+            style::fg::YELLOW,
+            "ComposedAuthenticationRequest",
+            style::fg::RESET,
+            req
+        );
+        let proto::ComposedAuthenticationRequest {
+            user,
+            proof,
+            timestamp,
+        } = req.into_inner();
+
+        info!(
+            "'{}{}{}' received for '{}{}{}'",
+            style::fg::YELLOW,
+            "ComposedAuthenticationRequest",
+            style::fg::RESET,
+            style::fg::CYAN,
+            user,
+            style::fg::RESET,
+        );
+
+        let now = self.check_timestamp(&user, timestamp)?;
+
+        let statements = self.user(&user).await?.composable();
+        let Some(statements) = statements else {
             error!(
-                "authentication challenge failed for user '{}{}{}'",
+                "user '{}{}{}' has credentials that can't be composed",
                 style::fg::CYAN,
-                user_id,
+                user,
                 style::fg::RESET
             );
-            Err(tonic::Status::unauthenticated(
-                "authentication challenge failed",
-            ))
+
+            return Err(tonic::Status::failed_precondition(
+                "composed logins need every credential to be a Chaum-Pedersen statement",
+            ));
+        };
+
+        let context = self.context.bind(&user, &login_context(timestamp));
+        let (commitment, verified) = match proof {
+            Some(proto::ComposedProof::All(proof)) => {
+                let proof: AndProof<G> = self.decode("all", Some(proof))?;
+                let commitment = composed_commitment(&*self.group, &proof.commitments);
+                self.check_unused(&user, now, &commitment).await?;
+                (
+                    commitment,
+                    proof.verify(&self.group, &statements, &user, &context),
+                )
+            }
+            Some(proto::ComposedProof::Any(proof)) => {
+                let proof: OrProof<G> = self.decode("any", Some(proof))?;
+                let commitment = composed_commitment(&*self.group, &proof.commitments);
+                self.check_unused(&user, now, &commitment).await?;
+                (
+                    commitment,
+                    proof.verify(&self.group, &statements, &user, &context),
+                )
+            }
+            None => {
+                let err = DecodeError::Missing("proof");
+                error!(
+                    "received a malformed '{}proof{}': {}",
+                    style::fg::YELLOW,
+                    style::fg::RESET,
+                    err
+                );
+
+                return Err(tonic::Status::invalid_argument(err.to_string()));
+            }
+        };
+
+        match verified {
+            Ok(()) => self.accept_proof(&user, commitment, timestamp).await,
+            Err(err) => {
+                error!(
+                    "composed proof failed for user '{}{}{}': {}",
+                    style::fg::CYAN,
+                    user,
+                    style::fg::RESET,
+                    err
+                );
+                Err(tonic::Status::unauthenticated(
+                    "authentication proof failed",
+                ))
+            }
         }
     }
+
+    async fn get_members(
+        &self,
+        req: tonic::Request<proto::MembersRequest>,
+    ) -> Result<tonic::Response<proto::MembersResponse>, tonic::Status> {
+        debug!(
+            "'{}{}{}' received: {:?}",
+            style::fg::YELLOW,
+            "MembersRequest",
+            style::fg::RESET,
+            req
+        );
+        let proto::MembersRequest {
+            users,
+            user,
+            session_id,
+        } = req.into_inner();
+
+        self.authenticated(&user, &session_id).await?;
+        let statements = self.ring_statements(&users).await?;
+
+        Ok(tonic::Response::new(proto::MembersResponse {
+            statements: statements
+                .iter()
+                .map(|statement| statement.to_proto(&*self.group))
+                .collect(),
+        }))
+    }
+
+    async fn verify_membership(
+        &self,
+        req: tonic::Request<proto::MembershipRequest>,
+    ) -> Result<tonic::Response<proto::MembershipResponse>, tonic::Status> {
+        debug!(
+            "'{}{}{}' received: {:?}",
+            style::fg::YELLOW,
+            "MembershipRequest",
+            style::fg::RESET,
+            req
+        );
+        let proto::MembershipRequest {
+            ring,
+            proof,
+            timestamp,
+        } = req.into_inner();
+
+        info!(
+            "'{}{}{}' received for a ring of {} users",
+            style::fg::YELLOW,
+            "MembershipRequest",
+            style::fg::RESET,
+            ring.len()
+        );
+
+        let now = unix_time();
+        if now.abs_diff(timestamp) > PROOF_VALIDITY {
+            error!("anonymous membership proof is stale");
+
+            return Err(tonic::Status::deadline_exceeded(
+                "proof timestamp is outside the validity window",
+            ));
+        }
+
+        let statements = self.ring_statements(&ring).await?;
+        let proof: OrProof<G> = self.decode("proof", proof)?;
+
+        let replayed = || {
+            error!("anonymous membership proof was replayed");
+
+            tonic::Status::unauthenticated("proof has already been used")
+        };
+
+        let commitment = composed_commitment(&*self.group, &proof.commitments);
+        if self.is_used(now, &commitment).await {
+            return Err(replayed());
+        }
+
+        // Membership proofs are bound to no user
+        let context = self.context.bind("", &membership_context(timestamp));
+        if let Err(err) = proof.verify_membership(&self.group, &statements, &context) {
+            error!("anonymous membership proof failed: {}", err);
+
+            return Err(tonic::Status::unauthenticated("membership proof failed"));
+        }
+
+        if !self.mark_used(commitment, timestamp).await {
+            return Err(replayed());
+        }
+
+        // Nothing about the token, nor the log, says which member proved
+        let token = self.rng.lock().await.alphanumeric(TOKEN_LEN);
+        self.store
+            .start_anonymous_session(&token)
+            .await
+            .map_err(store_failed)?;
+        info!(
+            "anonymous session issued to a member of a ring of {} users",
+            ring.len()
+        );
+
+        Ok(tonic::Response::new(proto::MembershipResponse { token }))
+    }
+}
+
+async fn serve<G: Group + Clone>(
+    group_id: GroupId,
+    group: &'static G,
+    listen: SocketAddr,
+    store_kind: &StoreKind,
+    challenge_ttl: u64,
+    context: Context,
+    rng: SecureRng,
+) -> anyhow::Result<()> {
+    group.validate()?;
+
+    info!(
+        "group fingerprint is '{}{}{}'",
+        style::fg::CYAN,
+        hex::encode(group.fingerprint()),
+        style::fg::RESET
+    );
+
+    // Every proof the server checks exponentiates both generators, so the tables pay for themselves quickly
+    let group = Arc::new(Precomputed::new(group.clone()));
+    let store = store_kind.open(group.clone())?;
+    info!(
+        "keeping users in '{}{}{}'",
+        style::fg::CYAN,
+        store_kind,
+        style::fg::RESET
+    );
+    let auth_service = AuthService::new(group_id, group, store, challenge_ttl, context, rng);
+
+    Server::builder()
+        .add_service(proto::AuthServer::new(auth_service))
+        .serve(listen)
+        .await?;
+
+    Ok(())
 }
 
 async fn init() -> anyhow::Result<()> {
     let args = cli::Args::parse();
 
-    let auth_service = AuthService::default();
-
     eprintln!("================== ZKP Auth (Server) ==================");
 
     println!(
-        "{}[i]{} Listening on '{}{}{}'",
+        "{}[i]{} Listening on '{}{}{}' using group '{}{}{}'",
         style::fg::GREEN,
         style::fg::RESET,
         style::fg::CYAN,
         args.listen,
+        style::fg::RESET,
+        style::fg::CYAN,
+        args.group,
         style::fg::RESET
     );
 
-    Server::builder()
-        .add_service(proto::AuthServer::new(auth_service))
-        .serve(args.listen)
-        .await?;
+    let rng = match args.insecure_seed {
+        Some(seed) => {
+            warn!(
+                "drawing challenges from a seeded RNG, anyone who knows the seed can predict them"
+            );
+            SecureRng::seeded(seed)
+        }
+        None => SecureRng::os(),
+    };
+
+    let context = Context::new(args.name, args.realm);
+
+    match args.group.resolve() {
+        NamedGroup::Modp(group) => {
+            serve(
+                args.group,
+                group,
+                args.listen,
+                &args.store,
+                args.challenge_ttl,
+                context,
+                rng,
+            )
+            .await?
+        }
+        #[cfg(feature = "ristretto")]
+        NamedGroup::Ristretto255(group) => {
+            serve(
+                args.group,
+                group,
+                args.listen,
+                &args.store,
+                args.challenge_ttl,
+                context,
+                rng,
+            )
+            .await?
+        }
+    }
 
     Ok(())
 }
@@ -317,3 +1131,217 @@ async fn main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proto::Auth;
+    use zkp_common::{consts, Parameters};
+
+    // Alice, Bob and Carol registered with Chaum-Pedersen, Dave with Schnorr. Only Alice is
+    // logged in.
+    struct Fixture {
+        service: AuthService<Parameters>,
+        group: Arc<Parameters>,
+        xs: Vec<<Parameters as Group>::Scalar>,
+        rng: SecureRng,
+    }
+
+    const RING: [&str; 3] = ["alice", "bob", "carol"];
+
+    const SESSION: &str = "alice-session";
+
+    async fn fixture() -> Fixture {
+        let group = Arc::new(consts::PARAMS.clone());
+        let store: Arc<dyn UserStore<Parameters>> = Arc::new(store::MemoryStore::default());
+        let mut rng = SecureRng::seeded(7);
+
+        let mut xs = Vec::new();
+        for user in RING {
+            let x = group.random_scalar(&mut rng);
+            let statement = Registered::ChaumPedersen(Statement::new(&*group, &x));
+            store.register(user, user_data(statement)).await.unwrap();
+            xs.push(x);
+        }
+        let x = group.random_scalar(&mut rng);
+        let statement =
+            Registered::Schnorr(<Schnorr as sigma::SigmaProtocol<_>>::statement(&*group, &x));
+        store.register("dave", user_data(statement)).await.unwrap();
+        store.start_session("alice", SESSION).await.unwrap();
+
+        let service = AuthService::new(
+            GroupId::Modp2048,
+            group.clone(),
+            store,
+            60,
+            Context::new("zkp-auth", "default"),
+            SecureRng::seeded(8),
+        );
+
+        Fixture {
+            service,
+            group,
+            xs,
+            rng,
+        }
+    }
+
+    fn user_data(statement: Registered<Parameters>) -> UserData<Parameters> {
+        UserData {
+            credentials: Credentials {
+                statement,
+                salt: Vec::new(),
+            },
+            additional: Vec::new(),
+        }
+    }
+
+    fn ring(users: &[&str]) -> Vec<UserName> {
+        users.iter().map(|user| user.to_string()).collect()
+    }
+
+    impl Fixture {
+        // A membership proof by `RING[index]` over the statements of `RING[..size]`, made at `timestamp`
+        fn request(
+            &mut self,
+            size: usize,
+            index: usize,
+            timestamp: u64,
+        ) -> proto::MembershipRequest {
+            let group = &*self.group;
+            let statements: Vec<_> = self.xs[..size]
+                .iter()
+                .map(|x| Statement::new(group, x))
+                .collect();
+            let context = self
+                .service
+                .context
+                .bind("", &membership_context(timestamp));
+            let proof = OrProof::membership(
+                group,
+                &statements,
+                index,
+                &self.xs[index],
+                &context,
+                &mut self.rng,
+            );
+
+            proto::MembershipRequest {
+                ring: ring(&RING),
+                proof: Some(proof.to_proto(group)),
+                timestamp,
+            }
+        }
+
+        async fn members(&self, users: &[&str]) -> Result<usize, tonic::Code> {
+            self.members_as("alice", SESSION, users).await
+        }
+
+        async fn members_as(
+            &self,
+            user: &str,
+            session_id: &str,
+            users: &[&str],
+        ) -> Result<usize, tonic::Code> {
+            self.service
+                .get_members(tonic::Request::new(proto::MembersRequest {
+                    users: ring(users),
+                    user: user.to_string(),
+                    session_id: session_id.to_string(),
+                }))
+                .await
+                .map(|response| response.into_inner().statements.len())
+                .map_err(|status| status.code())
+        }
+
+        async fn prove(&self, request: proto::MembershipRequest) -> Result<(), tonic::Code> {
+            self.service
+                .verify_membership(tonic::Request::new(request))
+                .await
+                .map(drop)
+                .map_err(|status| status.code())
+        }
+    }
+
+    #[tokio::test]
+    async fn ring_members() {
+        let fixture = fixture().await;
+
+        assert_eq!(fixture.members(&RING).await, Ok(3));
+        // Statements are for logged-in users only
+        for (user, session_id) in [("alice", ""), ("alice", "guess"), ("bob", SESSION)] {
+            assert_eq!(
+                fixture.members_as(user, session_id, &RING).await,
+                Err(tonic::Code::Unauthenticated)
+            );
+        }
+        assert_eq!(
+            fixture.members(&["alice", "mallory"]).await,
+            Err(tonic::Code::NotFound)
+        );
+        assert_eq!(
+            fixture.members(&["alice", "dave"]).await,
+            Err(tonic::Code::FailedPrecondition)
+        );
+        assert_eq!(
+            fixture.members(&["alice", "alice"]).await,
+            Err(tonic::Code::InvalidArgument)
+        );
+        // Rejected by size alone, before anyone is looked up
+        let users: Vec<_> = (0..=MAX_RING).map(|user| format!("user{}", user)).collect();
+        let users: Vec<_> = users.iter().map(String::as_str).collect();
+        assert_eq!(
+            fixture.members(&users).await,
+            Err(tonic::Code::InvalidArgument)
+        );
+    }
+
+    #[tokio::test]
+    async fn membership_proofs() {
+        let mut fixture = fixture().await;
+        let now = unix_time();
+
+        let request = fixture.request(3, 1, now);
+        assert_eq!(fixture.prove(request.clone()).await, Ok(()));
+        // The same proof can't be used twice
+        assert_eq!(
+            fixture.prove(request).await,
+            Err(tonic::Code::Unauthenticated)
+        );
+
+        // Rings must hold registered Chaum-Pedersen users only
+        let mut request = fixture.request(3, 2, now);
+        request.ring[0] = "mallory".to_string();
+        assert_eq!(fixture.prove(request).await, Err(tonic::Code::NotFound));
+        let mut request = fixture.request(3, 2, now);
+        request.ring[0] = "dave".to_string();
+        assert_eq!(
+            fixture.prove(request).await,
+            Err(tonic::Code::FailedPrecondition)
+        );
+
+        // A ring too large to look up, even if the proof were over all of it
+        let mut request = fixture.request(3, 0, now);
+        request.ring = (0..=MAX_RING).map(|user| format!("user{}", user)).collect();
+        assert_eq!(
+            fixture.prove(request).await,
+            Err(tonic::Code::InvalidArgument)
+        );
+
+        // A proof over fewer statements than the ring names
+        let request = fixture.request(2, 0, now);
+        assert_eq!(
+            fixture.prove(request).await,
+            Err(tonic::Code::Unauthenticated)
+        );
+
+        // Proofs made outside the validity window, either way
+        for timestamp in [now - 2 * PROOF_VALIDITY, now + 2 * PROOF_VALIDITY] {
+            let request = fixture.request(3, 0, timestamp);
+            assert_eq!(
+                fixture.prove(request).await,
+                Err(tonic::Code::DeadlineExceeded)
+            );
+        }
+    }
+}
@@ -0,0 +1,617 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::{debug, error};
+use tonic::async_trait;
+
+use zkp_common::group::Group;
+use zkp_common::proof::{DecodeError, Message, Statement};
+use zkp_common::proto;
+use zkp_common::protocol::ChallengedVerifier;
+use zkp_common::sigma::{self, okamoto, schnorr, Okamoto, ProtocolId, Schnorr};
+
+mod memory;
+mod redb;
+mod sqlite;
+
+pub use self::redb::RedbStore;
+pub use memory::MemoryStore;
+pub use sqlite::SqliteStore;
+
+pub type UserName = String;
+pub type AuthId = String;
+
+#[derive(Debug)]
+pub struct UserData<G: Group> {
+    pub credentials: Credentials<G>,
+    pub additional: Vec<Credentials<G>>, // only usable in composed logins
+}
+
+#[derive(Debug)]
+pub struct Credentials<G: Group> {
+    pub statement: Registered<G>,
+    pub salt: Vec<u8>,
+}
+
+// A user's statement, in the protocol they registered with
+#[derive(Debug)]
+pub enum Registered<G: Group> {
+    ChaumPedersen(Statement<G>),
+    Schnorr(schnorr::Statement<G>),
+    Okamoto(okamoto::Statement<G>),
+}
+
+// An interactive login waiting for its response, in the protocol its user registered with
+#[derive(Debug)]
+pub enum Challenged<G: Group> {
+    ChaumPedersen(ChallengedVerifier<G>),
+    Schnorr(sigma::ChallengedVerifier<G, Schnorr>),
+    Okamoto(sigma::ChallengedVerifier<G, Okamoto>),
+}
+
+// A challenge as it waits for its answer
+#[derive(Debug)]
+pub struct Issued<G: Group> {
+    pub user: UserName,
+    pub verifier: Challenged<G>,
+    pub expires: u64, // seconds since the UNIX epoch
+}
+
+// Deriving these would require the group itself to be `Clone`, not just its elements
+
+impl<G: Group> Clone for UserData<G> {
+    fn clone(&self) -> Self {
+        Self {
+            credentials: self.credentials.clone(),
+            additional: self.additional.clone(),
+        }
+    }
+}
+
+impl<G: Group> Clone for Credentials<G> {
+    fn clone(&self) -> Self {
+        Self {
+            statement: self.statement.clone(),
+            salt: self.salt.clone(),
+        }
+    }
+}
+
+impl<G: Group> Clone for Registered<G> {
+    fn clone(&self) -> Self {
+        match self {
+            Registered::ChaumPedersen(statement) => Registered::ChaumPedersen(statement.clone()),
+            Registered::Schnorr(statement) => Registered::Schnorr(statement.clone()),
+            Registered::Okamoto(statement) => Registered::Okamoto(statement.clone()),
+        }
+    }
+}
+
+impl<G: Group> UserData<G> {
+    // Every credential's statement, in registration order, if they're all Chaum-Pedersen
+    pub fn composable(&self) -> Option<Vec<Statement<G>>> {
+        std::iter::once(&self.credentials)
+            .chain(&self.additional)
+            .map(|credentials| match &credentials.statement {
+                Registered::ChaumPedersen(statement) => Some(statement.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl<G: Group> Registered<G> {
+    pub fn protocol(&self) -> ProtocolId {
+        match self {
+            Registered::ChaumPedersen(_) => ProtocolId::ChaumPedersen,
+            Registered::Schnorr(_) => ProtocolId::Schnorr,
+            Registered::Okamoto(_) => ProtocolId::Okamoto,
+        }
+    }
+
+    pub fn to_proto(&self, group: &G) -> proto::Statement {
+        match self {
+            Registered::ChaumPedersen(statement) => statement.to_proto(group),
+            Registered::Schnorr(statement) => statement.to_proto(group),
+            Registered::Okamoto(statement) => statement.to_proto(group),
+        }
+    }
+
+    pub fn from_proto(
+        group: &G,
+        protocol: ProtocolId,
+        message: proto::Statement,
+    ) -> Result<Self, DecodeError> {
+        Ok(match protocol {
+            ProtocolId::ChaumPedersen => Registered::ChaumPedersen((group, message).try_into()?),
+            ProtocolId::Schnorr => Registered::Schnorr((group, message).try_into()?),
+            ProtocolId::Okamoto => Registered::Okamoto((group, message).try_into()?),
+        })
+    }
+}
+
+impl<G: Group> Challenged<G> {
+    // The commitment and challenge, which together with the user's statement are the whole exchange
+    pub fn to_proto(&self, group: &G) -> (proto::Commitment, proto::Challenge) {
+        match self {
+            Challenged::ChaumPedersen(verifier) => (
+                verifier.commitment().to_proto(group),
+                verifier.challenge().to_proto(group),
+            ),
+            Challenged::Schnorr(verifier) => (
+                verifier.commitment().to_proto(group),
+                verifier.challenge().to_proto(group),
+            ),
+            Challenged::Okamoto(verifier) => (
+                verifier.commitment().to_proto(group),
+                verifier.challenge().to_proto(group),
+            ),
+        }
+    }
+
+    // Resumes the exchange for `statement`, checking the commitment is in its protocol
+    pub fn from_proto(
+        group: &G,
+        statement: Registered<G>,
+        commitment: proto::Commitment,
+        challenge: proto::Challenge,
+    ) -> Result<Self, DecodeError> {
+        let challenge = (group, challenge).try_into()?;
+
+        Ok(match statement {
+            Registered::ChaumPedersen(statement) => Challenged::ChaumPedersen(
+                ChallengedVerifier::resume(statement, (group, commitment).try_into()?, challenge),
+            ),
+            Registered::Schnorr(statement) => {
+                Challenged::Schnorr(sigma::ChallengedVerifier::resume(
+                    statement,
+                    (group, commitment).try_into()?,
+                    challenge,
+                ))
+            }
+            Registered::Okamoto(statement) => {
+                Challenged::Okamoto(sigma::ChallengedVerifier::resume(
+                    statement,
+                    (group, commitment).try_into()?,
+                    challenge,
+                ))
+            }
+        })
+    }
+}
+
+/// Why a store couldn't carry out an operation
+#[derive(Debug)]
+pub enum StoreError {
+    /// The backend itself failed
+    Backend(Box<dyn std::error::Error + Send + Sync>),
+    /// The stored records can't be used, say because they were written for another group
+    Unusable(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Backend(err) => write!(f, "store failed: {}", err),
+            StoreError::Unusable(why) => write!(f, "store is unusable: {}", why),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<DecodeError> for StoreError {
+    fn from(err: DecodeError) -> Self {
+        StoreError::Unusable(err.to_string())
+    }
+}
+
+/// Where the server keeps its users' credentials, the challenges it issued them and the
+/// sessions it started
+///
+/// Every operation is atomic on its own, so a backend can be shared by concurrent handlers.
+#[async_trait]
+pub trait UserStore<G: Group>: Send + Sync + 'static {
+    /// Register `user`, returning `false` (and storing nothing) if the name is taken
+    async fn register(&self, user: &str, data: UserData<G>) -> Result<bool, StoreError>;
+
+    /// The credentials `user` registered with, if they did
+    async fn user(&self, user: &str) -> Result<Option<UserData<G>>, StoreError>;
+
+    /// Record a challenge issued under `auth_id`, replacing any its user hadn't answered yet
+    async fn begin_challenge(&self, auth_id: &str, issued: Issued<G>) -> Result<(), StoreError>;
+
+    /// Take the challenge issued under `auth_id`, whether or not it has expired. It can only be
+    /// taken once, so it can only be answered once.
+    async fn take_challenge(&self, auth_id: &str) -> Result<Option<Issued<G>>, StoreError>;
+
+    /// Drop every challenge that expired before `now`, returning how many there were
+    async fn reap_challenges(&self, now: u64) -> Result<usize, StoreError>;
+
+    /// Start a session for `user`, replacing the one they had
+    async fn start_session(&self, user: &str, session_id: &str) -> Result<(), StoreError>;
+
+    /// The id of the session `user` last started, if they have one
+    async fn session(&self, user: &str) -> Result<Option<String>, StoreError>;
+
+    /// Start a session tied to no user
+    async fn start_anonymous_session(&self, token: &str) -> Result<(), StoreError>;
+}
+
+/// Which backend to keep users in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreKind {
+    /// In memory, lost when the server stops
+    Memory,
+    /// In an SQLite database at this path, created if missing
+    Sqlite(PathBuf),
+    /// In a redb file at this path, created if missing
+    Redb(PathBuf),
+}
+
+impl StoreKind {
+    pub fn open<G: Group>(&self, group: Arc<G>) -> Result<Arc<dyn UserStore<G>>, StoreError> {
+        Ok(match self {
+            StoreKind::Memory => Arc::new(MemoryStore::default()),
+            StoreKind::Sqlite(path) => Arc::new(SqliteStore::open(path, group)?),
+            StoreKind::Redb(path) => Arc::new(RedbStore::open(path, group)?),
+        })
+    }
+}
+
+impl fmt::Display for StoreKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreKind::Memory => f.write_str("memory"),
+            StoreKind::Sqlite(path) => write!(f, "sqlite:{}", path.display()),
+            StoreKind::Redb(path) => write!(f, "redb:{}", path.display()),
+        }
+    }
+}
+
+impl FromStr for StoreKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "memory" => Ok(StoreKind::Memory),
+            Some(("sqlite", path)) if !path.is_empty() => Ok(StoreKind::Sqlite(path.into())),
+            Some(("redb", path)) if !path.is_empty() => Ok(StoreKind::Redb(path.into())),
+            _ => Err(format!(
+                "unknown store '{}', expected `memory`, `sqlite:<PATH>` or `redb:<PATH>`",
+                s
+            )),
+        }
+    }
+}
+
+/// Sweep expired challenges out of `store` every `period`, for as long as it's around, so
+/// abandoned logins don't pile up
+///
+/// Challenges are only swept once they've been expired for a whole period, so an answer that
+/// comes in late still finds its challenge and is told it expired, rather than that there
+/// never was one.
+pub async fn reap_challenges<G: Group>(store: Weak<dyn UserStore<G>>, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    // The first tick completes right away
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        let Some(store) = store.upgrade() else {
+            break;
+        };
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        match store
+            .reap_challenges(now.saturating_sub(period.as_secs()))
+            .await
+        {
+            Ok(0) => {}
+            Ok(reaped) => debug!("reaped {} expired authentication challenges", reaped),
+            Err(err) => error!("failed to reap expired authentication challenges: {}", err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zkp_common::proof::{Challenge, Commitment};
+    use zkp_common::{consts, Parameters};
+    use zkp_utils::random::SecureRng;
+
+    type G = Parameters;
+
+    // Far enough ahead (in 2096) that nothing expires while the checks run
+    const LATER: u64 = 4_000_000_000;
+
+    fn group() -> Arc<G> {
+        Arc::new(consts::PARAMS.clone())
+    }
+
+    // A Chaum-Pedersen password and two additional credentials, in other protocols so that
+    // their order shows
+    fn user_data(group: &G, rng: &mut SecureRng) -> UserData<G> {
+        let mut scalar = || group.random_scalar(rng);
+        let (x, x1, x2) = (scalar(), scalar(), [scalar(), scalar()]);
+
+        UserData {
+            credentials: Credentials {
+                statement: Registered::ChaumPedersen(Statement::new(group, &x)),
+                salt: b"password".to_vec(),
+            },
+            additional: vec![
+                Credentials {
+                    statement: Registered::Schnorr(schnorr::Statement::new(group, &x1)),
+                    salt: b"schnorr".to_vec(),
+                },
+                Credentials {
+                    statement: Registered::Okamoto(okamoto::Statement::new(group, &x2)),
+                    salt: b"okamoto".to_vec(),
+                },
+            ],
+        }
+    }
+
+    // Every credential as it's encoded, in order, to compare what went in with what came out
+    fn encoded(group: &G, data: &UserData<G>) -> Vec<(ProtocolId, proto::Statement, Vec<u8>)> {
+        std::iter::once(&data.credentials)
+            .chain(&data.additional)
+            .map(|credentials| {
+                (
+                    credentials.statement.protocol(),
+                    credentials.statement.to_proto(group),
+                    credentials.salt.clone(),
+                )
+            })
+            .collect()
+    }
+
+    // A challenge issued against the user's password
+    fn issued(
+        group: &G,
+        data: &UserData<G>,
+        user: &str,
+        expires: u64,
+        rng: &mut SecureRng,
+    ) -> Issued<G> {
+        let Registered::ChaumPedersen(statement) = data.credentials.statement.clone() else {
+            unreachable!("passwords are Chaum-Pedersen");
+        };
+        let commitment = Commitment::new(group, &group.random_scalar(rng));
+
+        Issued {
+            user: user.to_string(),
+            verifier: Challenged::ChaumPedersen(ChallengedVerifier::resume(
+                statement,
+                commitment,
+                Challenge::random(group, rng),
+            )),
+            expires,
+        }
+    }
+
+    type EncodedChallenge = (UserName, proto::Commitment, proto::Challenge, u64);
+
+    fn encoded_challenge(group: &G, issued: &Issued<G>) -> EncodedChallenge {
+        let (commitment, challenge) = issued.verifier.to_proto(group);
+        (issued.user.clone(), commitment, challenge, issued.expires)
+    }
+
+    async fn take(store: &dyn UserStore<G>, group: &G, auth_id: &str) -> Option<EncodedChallenge> {
+        store
+            .take_challenge(auth_id)
+            .await
+            .unwrap()
+            .map(|issued| encoded_challenge(group, &issued))
+    }
+
+    // What every backend must do
+    async fn check_store(store: &dyn UserStore<G>, group: &G) {
+        let mut rng = SecureRng::seeded(1);
+        let alice = user_data(group, &mut rng);
+        let bob = user_data(group, &mut rng);
+
+        // Names are taken once, and a second registration stores nothing
+        assert!(store.register("alice", alice.clone()).await.unwrap());
+        assert!(!store.register("alice", bob.clone()).await.unwrap());
+        assert!(store.register("bob", bob.clone()).await.unwrap());
+
+        for (user, data) in [("alice", &alice), ("bob", &bob)] {
+            let stored = store.user(user).await.unwrap().unwrap();
+            assert_eq!(encoded(group, &stored), encoded(group, data));
+        }
+        assert!(store.user("carol").await.unwrap().is_none());
+
+        // A new challenge replaces the user's unanswered one, but not anyone else's
+        let first = issued(group, &alice, "alice", LATER, &mut rng);
+        let second = issued(group, &alice, "alice", LATER, &mut rng);
+        let other = issued(group, &bob, "bob", LATER, &mut rng);
+        let (second_encoded, other_encoded) = (
+            encoded_challenge(group, &second),
+            encoded_challenge(group, &other),
+        );
+        store.begin_challenge("first", first).await.unwrap();
+        store.begin_challenge("other", other).await.unwrap();
+        store.begin_challenge("second", second).await.unwrap();
+        assert_eq!(take(store, group, "first").await, None);
+
+        // Each challenge can be taken once
+        assert_eq!(take(store, group, "second").await, Some(second_encoded));
+        assert_eq!(take(store, group, "second").await, None);
+        assert_eq!(take(store, group, "other").await, Some(other_encoded));
+        assert_eq!(take(store, group, "other").await, None);
+
+        // Challenges stay until they're reaped, expired or not, and only expired ones are reaped
+        let (stale, due, late) = (
+            issued(group, &alice, "alice", 100, &mut rng),
+            issued(group, &bob, "bob", 200, &mut rng),
+            issued(group, &alice, "alice", 100, &mut rng),
+        );
+        let (due_encoded, late_encoded) = (
+            encoded_challenge(group, &due),
+            encoded_challenge(group, &late),
+        );
+        store.begin_challenge("stale", stale).await.unwrap();
+        store.begin_challenge("due", due).await.unwrap();
+        assert_eq!(store.reap_challenges(200).await.unwrap(), 1);
+        assert_eq!(store.reap_challenges(200).await.unwrap(), 0);
+        assert_eq!(take(store, group, "stale").await, None);
+        assert_eq!(take(store, group, "due").await, Some(due_encoded));
+
+        store.begin_challenge("late", late).await.unwrap();
+        assert_eq!(take(store, group, "late").await, Some(late_encoded));
+        assert_eq!(store.reap_challenges(LATER).await.unwrap(), 0);
+
+        assert_eq!(store.session("alice").await.unwrap(), None);
+        store.start_session("alice", "session").await.unwrap();
+        store.start_session("alice", "replaced").await.unwrap();
+        assert_eq!(
+            store.session("alice").await.unwrap().as_deref(),
+            Some("replaced")
+        );
+        store.start_anonymous_session("token").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn memory_store() {
+        check_store(&MemoryStore::default(), &group()).await;
+    }
+
+    #[tokio::test]
+    async fn sqlite_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.db");
+        let group = group();
+
+        check_store(&SqliteStore::open(&path, group.clone()).unwrap(), &group).await;
+
+        // Reopening keeps everything
+        let store = SqliteStore::open(&path, group.clone()).unwrap();
+        assert!(store.user("alice").await.unwrap().is_some());
+        drop(store);
+
+        // The file was made for another group
+        let other = Arc::new(consts::FFDHE_2048.clone());
+        assert!(matches!(
+            SqliteStore::open(&path, other),
+            Err(StoreError::Unusable(_))
+        ));
+
+        // The schema is newer than this server knows
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        connection
+            .pragma_update(None, "user_version", sqlite::MIGRATIONS.len() + 1)
+            .unwrap();
+        drop(connection);
+        assert!(matches!(
+            SqliteStore::open(&path, group),
+            Err(StoreError::Unusable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn redb_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.redb");
+        let group = group();
+
+        check_store(&RedbStore::open(&path, group.clone()).unwrap(), &group).await;
+
+        // Reopening keeps everything
+        let store = RedbStore::open(&path, group.clone()).unwrap();
+        let mut rng = SecureRng::seeded(2);
+        let alice = store.user("alice").await.unwrap().unwrap();
+        let carol = user_data(&group, &mut rng);
+
+        // Compaction waits for the operations in flight, holds off the others and loses nothing
+        let (compacted, registered, read) = tokio::join!(
+            store.compact(),
+            store.register("carol", carol.clone()),
+            store.user("bob"),
+        );
+        compacted.unwrap();
+        assert!(registered.unwrap());
+        assert!(read.unwrap().is_some());
+        store.compact().await.unwrap();
+        for (user, data) in [("alice", &alice), ("carol", &carol)] {
+            let stored = store.user(user).await.unwrap().unwrap();
+            assert_eq!(encoded(&group, &stored), encoded(&group, data));
+        }
+        drop(store);
+
+        // The file was made for another group
+        let other = Arc::new(consts::FFDHE_2048.clone());
+        assert!(matches!(
+            RedbStore::open(&path, other),
+            Err(StoreError::Unusable(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn sqlite_migration() {
+        use prost::Message as _;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.db");
+        let group = group();
+        let mut rng = SecureRng::seeded(3);
+        let alice = user_data(&group, &mut rng);
+        let issued = issued(&group, &alice, "alice", LATER, &mut rng);
+        let (commitment, challenge) = issued.verifier.to_proto(&*group);
+
+        // A challenge issued by a server from before challenges expired
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        connection.execute_batch(sqlite::MIGRATIONS[0]).unwrap();
+        connection
+            .execute(
+                "INSERT INTO meta (key, value) VALUES ('group', ?1)",
+                [group.fingerprint().to_vec()],
+            )
+            .unwrap();
+        connection
+            .execute(
+                "INSERT INTO users (name, protocol, statement, salt) VALUES ('alice', ?1, ?2, ?3)",
+                rusqlite::params![
+                    alice.credentials.statement.protocol().to_string(),
+                    alice
+                        .credentials
+                        .statement
+                        .to_proto(&*group)
+                        .encode_to_vec(),
+                    alice.credentials.salt,
+                ],
+            )
+            .unwrap();
+        connection
+            .execute(
+                "INSERT INTO challenges (auth_id, user, commitment, challenge)
+                    VALUES ('old', 'alice', ?1, ?2)",
+                [commitment.encode_to_vec(), challenge.encode_to_vec()],
+            )
+            .unwrap();
+        connection.pragma_update(None, "user_version", 1).unwrap();
+        drop(connection);
+
+        // It comes back long expired
+        let store = SqliteStore::open(&path, group.clone()).unwrap();
+        let old = store.take_challenge("old").await.unwrap().unwrap();
+        assert_eq!(
+            encoded_challenge(&group, &old),
+            ("alice".to_string(), commitment, challenge, 0)
+        );
+        drop(store);
+
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        let version: usize = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, sqlite::MIGRATIONS.len());
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::{HashMap, HashSet};
+
+use tokio::sync::RwLock;
+use tonic::async_trait;
+
+use super::{AuthId, Issued, StoreError, UserData, UserName, UserStore};
+use zkp_common::group::Group;
+
+/// Keeps everything in memory, so every restart starts from scratch
+pub struct MemoryStore<G: Group> {
+    users: RwLock<HashMap<UserName, UserData<G>>>,
+    auth_pairs: RwLock<HashMap<AuthId, UserName>>,
+    challenges: RwLock<HashMap<UserName, (AuthId, Issued<G>)>>, // at most one per user
+    sessions: RwLock<HashMap<UserName, String>>,
+    anonymous_sessions: RwLock<HashSet<String>>, // tokens issued for membership proofs, tied to no user
+}
+
+impl<G: Group> Default for MemoryStore<G> {
+    fn default() -> Self {
+        Self {
+            users: RwLock::default(),
+            auth_pairs: RwLock::default(),
+            challenges: RwLock::default(),
+            sessions: RwLock::default(),
+            anonymous_sessions: RwLock::default(),
+        }
+    }
+}
+
+#[async_trait]
+impl<G: Group> UserStore<G> for MemoryStore<G> {
+    async fn register(&self, user: &str, data: UserData<G>) -> Result<bool, StoreError> {
+        let mut users = self.users.write().await;
+        if users.contains_key(user) {
+            return Ok(false);
+        }
+
+        users.insert(user.to_string(), data);
+        Ok(true)
+    }
+
+    async fn user(&self, user: &str) -> Result<Option<UserData<G>>, StoreError> {
+        Ok(self.users.read().await.get(user).cloned())
+    }
+
+    async fn begin_challenge(&self, auth_id: &str, issued: Issued<G>) -> Result<(), StoreError> {
+        let mut auth_pairs = self.auth_pairs.write().await;
+        let mut challenges = self.challenges.write().await;
+
+        let user = issued.user.clone();
+        if let Some((replaced, _)) = challenges.insert(user.clone(), (auth_id.to_string(), issued))
+        {
+            auth_pairs.remove(&replaced);
+        }
+        auth_pairs.insert(auth_id.to_string(), user);
+
+        Ok(())
+    }
+
+    async fn take_challenge(&self, auth_id: &str) -> Result<Option<Issued<G>>, StoreError> {
+        let mut auth_pairs = self.auth_pairs.write().await;
+        let mut challenges = self.challenges.write().await;
+
+        let Some(user) = auth_pairs.remove(auth_id) else {
+            return Ok(None);
+        };
+
+        Ok(challenges.remove(&user).map(|(_, issued)| issued))
+    }
+
+    async fn reap_challenges(&self, now: u64) -> Result<usize, StoreError> {
+        let mut auth_pairs = self.auth_pairs.write().await;
+        let mut challenges = self.challenges.write().await;
+
+        let before = challenges.len();
+        challenges.retain(|_, (auth_id, issued)| {
+            let live = issued.expires >= now;
+            if !live {
+                auth_pairs.remove(auth_id);
+            }
+            live
+        });
+
+        Ok(before - challenges.len())
+    }
+
+    async fn start_session(&self, user: &str, session_id: &str) -> Result<(), StoreError> {
+        self.sessions
+            .write()
+            .await
+            .insert(user.to_string(), session_id.to_string());
+
+        Ok(())
+    }
+
+    async fn session(&self, user: &str) -> Result<Option<String>, StoreError> {
+        Ok(self.sessions.read().await.get(user).cloned())
+    }
+
+    async fn start_anonymous_session(&self, token: &str) -> Result<(), StoreError> {
+        self.anonymous_sessions
+            .write()
+            .await
+            .insert(token.to_string());
+
+        Ok(())
+    }
+}
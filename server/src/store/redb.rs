@@ -0,0 +1,433 @@
+use std::path::Path;
+use std::sync::{Arc, RwLock, Weak};
+use std::time::Duration;
+
+use log::{debug, error, info};
+use prost::Message as _;
+use redb::{Database, ReadableTable, TableDefinition};
+use tonic::async_trait;
+
+use super::{Challenged, Credentials, Issued, Registered, StoreError, UserData, UserStore};
+use zkp_common::group::Group;
+use zkp_common::{proto, sigma};
+
+// Records are protobuf-encoded, so fields can be added to them later without rewriting the file
+const META: TableDefinition<&str, &[u8]> = TableDefinition::new("meta");
+const USERS: TableDefinition<&str, &[u8]> = TableDefinition::new("users"); // name -> UserRecord
+const CHALLENGES: TableDefinition<&str, &[u8]> = TableDefinition::new("challenges"); // auth id -> ChallengeRecord
+const PENDING: TableDefinition<&str, &str> = TableDefinition::new("pending"); // name -> auth id, at most one each
+const SESSIONS: TableDefinition<&str, &str> = TableDefinition::new("sessions"); // name -> session id
+const ANONYMOUS_SESSIONS: TableDefinition<&str, ()> = TableDefinition::new("anonymous_sessions");
+
+// How often the file is compacted, giving back the space freed by answered and expired challenges
+const COMPACTION_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct UserRecord {
+    /// The primary credentials first, then the additional ones in registration order
+    #[prost(message, repeated, tag = "1")]
+    credentials: Vec<CredentialRecord>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct CredentialRecord {
+    #[prost(string, tag = "1")]
+    protocol: String,
+    #[prost(message, optional, tag = "2")]
+    statement: Option<proto::Statement>,
+    #[prost(bytes = "vec", tag = "3")]
+    salt: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ChallengeRecord {
+    #[prost(string, tag = "1")]
+    user: String,
+    #[prost(message, optional, tag = "2")]
+    commitment: Option<proto::Commitment>,
+    #[prost(message, optional, tag = "3")]
+    challenge: Option<proto::Challenge>,
+    /// Seconds since the UNIX epoch; records from before this was tracked decode as long expired
+    #[prost(uint64, tag = "4")]
+    expires: u64,
+}
+
+impl From<redb::Error> for StoreError {
+    fn from(err: redb::Error) -> Self {
+        StoreError::Backend(err.into())
+    }
+}
+
+/// Keeps everything in a single redb file, an embedded pure-Rust key-value store, so
+/// registrations outlive the server without shipping SQLite
+///
+/// Every operation is one transaction, committed durably before it returns, so a crash leaves
+/// the file as it was after the last one. A background task compacts the file on startup and
+/// every hour after, while the server keeps running.
+pub struct RedbStore<G: Group> {
+    group: Arc<G>,
+    // Compaction needs the database to itself, everything else shares it
+    database: Arc<RwLock<Database>>,
+}
+
+impl<G: Group> RedbStore<G> {
+    /// Open the file at `path`, creating it if missing, and start compacting it
+    ///
+    /// Spawns the compaction task, so this must run inside the runtime. Fails if the file was
+    /// created for another group, whose statements would never decode.
+    pub fn open(path: &Path, group: Arc<G>) -> Result<Self, StoreError> {
+        let database = Database::create(path).map_err(redb::Error::from)?;
+        let fingerprint = group.fingerprint();
+
+        let stored = init(&database, &fingerprint)?;
+        if stored[..] != fingerprint[..] {
+            return Err(StoreError::Unusable(format!(
+                "'{}' holds users of the group with fingerprint '{}', not '{}'",
+                path.display(),
+                hex::encode(stored),
+                hex::encode(fingerprint)
+            )));
+        }
+
+        let database = Arc::new(RwLock::new(database));
+        tokio::spawn(compact(Arc::downgrade(&database)));
+
+        Ok(Self { group, database })
+    }
+
+    // Runs `f` against the database off the async runtime, as redb blocks
+    async fn run<T, F>(&self, f: F) -> Result<T, StoreError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Database) -> Result<T, redb::Error> + Send + 'static,
+    {
+        let database = self.database.clone();
+        tokio::task::spawn_blocking(move || {
+            // An uncommitted transaction is rolled back when a panic drops it
+            let database = database
+                .read()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            f(&database)
+        })
+        .await
+        .map_err(|err| StoreError::Backend(err.into()))?
+        .map_err(StoreError::from)
+    }
+
+    // Compacts the file now rather than waiting for the background task, which only tests need
+    #[cfg(test)]
+    pub async fn compact(&self) -> Result<bool, StoreError> {
+        compact_now(self.database.clone()).await
+    }
+
+    fn encode(&self, credentials: &Credentials<G>) -> CredentialRecord {
+        CredentialRecord {
+            protocol: credentials.statement.protocol().to_string(),
+            statement: Some(credentials.statement.to_proto(&*self.group)),
+            salt: credentials.salt.clone(),
+        }
+    }
+
+    fn decode(&self, record: CredentialRecord) -> Result<Credentials<G>, StoreError> {
+        let protocol = record
+            .protocol
+            .parse()
+            .map_err(|err: sigma::UnknownProtocol| StoreError::Unusable(err.to_string()))?;
+        let statement = record.statement.ok_or_else(missing("statement"))?;
+
+        Ok(Credentials {
+            statement: Registered::from_proto(&*self.group, protocol, statement)?,
+            salt: record.salt,
+        })
+    }
+
+    fn decode_user(&self, bytes: &[u8]) -> Result<UserData<G>, StoreError> {
+        let mut credentials = decode_record::<UserRecord>(bytes)?
+            .credentials
+            .into_iter()
+            .map(|record| self.decode(record));
+        let primary = credentials.next().ok_or_else(missing("credentials"))??;
+
+        Ok(UserData {
+            credentials: primary,
+            additional: credentials.collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+// Creates every table, as reading transactions expect them to be there, and records the group
+// the first time round. Returns the group the file was created for.
+fn init(database: &Database, fingerprint: &[u8]) -> Result<Vec<u8>, redb::Error> {
+    let transaction = database.begin_write()?;
+    let stored = {
+        let mut meta = transaction.open_table(META)?;
+        let stored = meta.get("group")?.map(|stored| stored.value().to_vec());
+        if stored.is_none() {
+            meta.insert("group", fingerprint)?;
+        }
+
+        transaction.open_table(USERS)?;
+        transaction.open_table(CHALLENGES)?;
+        transaction.open_table(PENDING)?;
+        transaction.open_table(SESSIONS)?;
+        transaction.open_table(ANONYMOUS_SESSIONS)?;
+
+        stored
+    };
+    transaction.commit()?;
+
+    Ok(stored.unwrap_or_else(|| fingerprint.to_vec()))
+}
+
+fn decode_record<M: prost::Message + Default>(bytes: &[u8]) -> Result<M, StoreError> {
+    M::decode(bytes).map_err(|err| StoreError::Unusable(err.to_string()))
+}
+
+fn missing(field: &'static str) -> impl FnOnce() -> StoreError {
+    move || StoreError::Unusable(format!("record has no '{}'", field))
+}
+
+// Compacts the file right away, then every `COMPACTION_INTERVAL` for as long as the store is around
+async fn compact(database: Weak<RwLock<Database>>) {
+    let mut interval = tokio::time::interval(COMPACTION_INTERVAL);
+
+    loop {
+        interval.tick().await;
+        let Some(database) = database.upgrade() else {
+            break;
+        };
+
+        match compact_now(database).await {
+            Ok(true) => info!("compacted the user store"),
+            Ok(false) => debug!("user store had nothing to compact"),
+            Err(err) => error!("failed to compact the user store: {}", err),
+        }
+    }
+}
+
+// Returns whether there was anything to give back
+async fn compact_now(database: Arc<RwLock<Database>>) -> Result<bool, StoreError> {
+    tokio::task::spawn_blocking(move || {
+        // Waits for the transactions in flight, and holds off new ones until it's done
+        let mut database = database
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        database.compact()
+    })
+    .await
+    .map_err(|err| StoreError::Backend(err.into()))?
+    .map_err(|err| StoreError::Backend(err.into()))
+}
+
+#[async_trait]
+impl<G: Group> UserStore<G> for RedbStore<G> {
+    async fn register(&self, user: &str, data: UserData<G>) -> Result<bool, StoreError> {
+        let user = user.to_string();
+        let record = UserRecord {
+            credentials: std::iter::once(&data.credentials)
+                .chain(&data.additional)
+                .map(|credentials| self.encode(credentials))
+                .collect(),
+        }
+        .encode_to_vec();
+
+        self.run(move |database| {
+            let transaction = database.begin_write()?;
+            {
+                let mut users = transaction.open_table(USERS)?;
+                if users.get(user.as_str())?.is_some() {
+                    return Ok(false);
+                }
+                users.insert(user.as_str(), record.as_slice())?;
+            }
+            transaction.commit()?;
+
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn user(&self, user: &str) -> Result<Option<UserData<G>>, StoreError> {
+        let user = user.to_string();
+        let record = self
+            .run(move |database| {
+                let transaction = database.begin_read()?;
+                let users = transaction.open_table(USERS)?;
+                let record = users.get(user.as_str())?;
+
+                Ok(record.map(|record| record.value().to_vec()))
+            })
+            .await?;
+
+        record.map(|record| self.decode_user(&record)).transpose()
+    }
+
+    async fn begin_challenge(&self, auth_id: &str, issued: Issued<G>) -> Result<(), StoreError> {
+        let (auth_id, user) = (auth_id.to_string(), issued.user);
+        let (commitment, challenge) = issued.verifier.to_proto(&*self.group);
+        let record = ChallengeRecord {
+            user: user.clone(),
+            commitment: Some(commitment),
+            challenge: Some(challenge),
+            expires: issued.expires,
+        }
+        .encode_to_vec();
+
+        self.run(move |database| {
+            let transaction = database.begin_write()?;
+            {
+                let mut challenges = transaction.open_table(CHALLENGES)?;
+                let mut pending = transaction.open_table(PENDING)?;
+                // The user's unanswered challenge, if any, is replaced
+                if let Some(replaced) = pending.insert(user.as_str(), auth_id.as_str())? {
+                    challenges.remove(replaced.value())?;
+                }
+                challenges.insert(auth_id.as_str(), record.as_slice())?;
+            }
+            transaction.commit()?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn take_challenge(&self, auth_id: &str) -> Result<Option<Issued<G>>, StoreError> {
+        let auth_id = auth_id.to_string();
+        let records = self
+            .run(move |database| {
+                let transaction = database.begin_write()?;
+                let records = {
+                    let mut challenges = transaction.open_table(CHALLENGES)?;
+                    let Some(challenge) = challenges
+                        .remove(auth_id.as_str())?
+                        .map(|challenge| challenge.value().to_vec())
+                    else {
+                        return Ok(None);
+                    };
+                    let record = ChallengeRecord::decode(challenge.as_slice()).ok();
+
+                    // A record that doesn't decode is dropped all the same
+                    let user = match &record {
+                        Some(record) => {
+                            let mut pending = transaction.open_table(PENDING)?;
+                            pending.remove(record.user.as_str())?;
+                            transaction
+                                .open_table(USERS)?
+                                .get(record.user.as_str())?
+                                .map(|user| user.value().to_vec())
+                        }
+                        None => None,
+                    };
+
+                    Some((challenge, user))
+                };
+                transaction.commit()?;
+
+                Ok(records)
+            })
+            .await?;
+
+        let Some((challenge, user)) = records else {
+            return Ok(None);
+        };
+        let challenge = decode_record::<ChallengeRecord>(&challenge)?;
+        let user = user.ok_or_else(|| {
+            StoreError::Unusable(format!(
+                "challenge was issued to '{}', who isn't registered",
+                challenge.user
+            ))
+        })?;
+        let statement = self.decode_user(&user)?.credentials.statement;
+        let verifier = Challenged::from_proto(
+            &*self.group,
+            statement,
+            challenge.commitment.ok_or_else(missing("commitment"))?,
+            challenge.challenge.ok_or_else(missing("challenge"))?,
+        )?;
+
+        Ok(Some(Issued {
+            user: challenge.user,
+            verifier,
+            expires: challenge.expires,
+        }))
+    }
+
+    async fn reap_challenges(&self, now: u64) -> Result<usize, StoreError> {
+        self.run(move |database| {
+            let transaction = database.begin_write()?;
+            let reaped = {
+                let mut challenges = transaction.open_table(CHALLENGES)?;
+                let mut pending = transaction.open_table(PENDING)?;
+
+                // Records that don't decode are reaped too, as nothing could answer them
+                let mut expired = Vec::new();
+                for entry in challenges.iter()? {
+                    let (auth_id, record) = entry?;
+                    match ChallengeRecord::decode(record.value()) {
+                        Ok(record) if record.expires >= now => {}
+                        Ok(record) => {
+                            expired.push((auth_id.value().to_string(), Some(record.user)))
+                        }
+                        Err(_) => expired.push((auth_id.value().to_string(), None)),
+                    }
+                }
+
+                for (auth_id, user) in &expired {
+                    challenges.remove(auth_id.as_str())?;
+                    if let Some(user) = user {
+                        pending.remove(user.as_str())?;
+                    }
+                }
+
+                expired.len()
+            };
+            transaction.commit()?;
+
+            Ok(reaped)
+        })
+        .await
+    }
+
+    async fn start_session(&self, user: &str, session_id: &str) -> Result<(), StoreError> {
+        let (user, session_id) = (user.to_string(), session_id.to_string());
+
+        self.run(move |database| {
+            let transaction = database.begin_write()?;
+            transaction
+                .open_table(SESSIONS)?
+                .insert(user.as_str(), session_id.as_str())?;
+            transaction.commit()?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn session(&self, user: &str) -> Result<Option<String>, StoreError> {
+        let user = user.to_string();
+
+        self.run(move |database| {
+            let transaction = database.begin_read()?;
+            let sessions = transaction.open_table(SESSIONS)?;
+            let session_id = sessions.get(user.as_str())?;
+
+            Ok(session_id.map(|session_id| session_id.value().to_string()))
+        })
+        .await
+    }
+
+    async fn start_anonymous_session(&self, token: &str) -> Result<(), StoreError> {
+        let token = token.to_string();
+
+        self.run(move |database| {
+            let transaction = database.begin_write()?;
+            transaction
+                .open_table(ANONYMOUS_SESSIONS)?
+                .insert(token.as_str(), ())?;
+            transaction.commit()?;
+
+            Ok(())
+        })
+        .await
+    }
+}
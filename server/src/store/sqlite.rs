@@ -0,0 +1,376 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use prost::Message as _;
+use rusqlite::{params, Connection, OptionalExtension};
+use tonic::async_trait;
+
+use super::{
+    Challenged, Credentials, Issued, Registered, StoreError, UserData, UserName, UserStore,
+};
+use zkp_common::group::Group;
+use zkp_common::sigma;
+
+// Each entry upgrades the schema by one version, which is kept in `PRAGMA user_version`.
+// Released entries must never change: append a new one instead.
+pub(super) const MIGRATIONS: &[&str] = &[
+    // 1: users, their challenges and sessions, and the group it's all encoded for
+    "CREATE TABLE meta (
+        key TEXT PRIMARY KEY,
+        value BLOB NOT NULL
+    );
+    CREATE TABLE users (
+        name TEXT PRIMARY KEY,
+        protocol TEXT NOT NULL,
+        statement BLOB NOT NULL,
+        salt BLOB NOT NULL
+    );
+    CREATE TABLE credentials (
+        user TEXT NOT NULL REFERENCES users (name),
+        position INTEGER NOT NULL,
+        protocol TEXT NOT NULL,
+        statement BLOB NOT NULL,
+        salt BLOB NOT NULL,
+        PRIMARY KEY (user, position)
+    );
+    CREATE TABLE challenges (
+        auth_id TEXT PRIMARY KEY,
+        user TEXT NOT NULL UNIQUE REFERENCES users (name),
+        commitment BLOB NOT NULL,
+        challenge BLOB NOT NULL
+    );
+    CREATE TABLE sessions (
+        user TEXT PRIMARY KEY REFERENCES users (name),
+        session_id TEXT NOT NULL
+    );
+    CREATE TABLE anonymous_sessions (
+        token TEXT PRIMARY KEY
+    );",
+    // 2: when each challenge expires, in seconds since the UNIX epoch. Those issued before this
+    // was tracked are long expired.
+    "ALTER TABLE challenges ADD COLUMN expires INTEGER NOT NULL DEFAULT 0;
+    CREATE INDEX challenges_expires ON challenges (expires);",
+];
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(err: rusqlite::Error) -> Self {
+        StoreError::Backend(err.into())
+    }
+}
+
+/// Keeps everything in an SQLite database, so registrations outlive the server
+///
+/// Statements, commitments and challenges are stored protobuf-encoded, as they travel.
+pub struct SqliteStore<G: Group> {
+    group: Arc<G>,
+    connection: Arc<Mutex<Connection>>,
+}
+
+// A statement as stored: its protocol and the encoded proto::Statement
+type Row = (String, Vec<u8>);
+
+impl<G: Group> SqliteStore<G> {
+    /// Open the database at `path`, creating it if missing and bringing its schema up to date
+    ///
+    /// Fails if the database was created for another group, whose statements would never decode.
+    pub fn open(path: &Path, group: Arc<G>) -> Result<Self, StoreError> {
+        let mut connection = Connection::open(path)?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        migrate(&mut connection)?;
+
+        let fingerprint = group.fingerprint().to_vec();
+        let stored: Option<Vec<u8>> = connection
+            .query_row("SELECT value FROM meta WHERE key = 'group'", [], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        match stored {
+            Some(stored) if stored != fingerprint => {
+                return Err(StoreError::Unusable(format!(
+                    "'{}' holds users of the group with fingerprint '{}', not '{}'",
+                    path.display(),
+                    hex::encode(stored),
+                    hex::encode(fingerprint)
+                )));
+            }
+            Some(_) => {}
+            None => {
+                connection.execute(
+                    "INSERT INTO meta (key, value) VALUES ('group', ?1)",
+                    params![fingerprint],
+                )?;
+            }
+        }
+
+        Ok(Self {
+            group,
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    // Runs `f` against the connection off the async runtime, as SQLite blocks
+    async fn run<T, F>(&self, f: F) -> Result<T, StoreError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> rusqlite::Result<T> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+        tokio::task::spawn_blocking(move || {
+            // A panic mid-transaction rolls it back, so the connection is still good
+            let mut connection = connection
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            f(&mut connection)
+        })
+        .await
+        .map_err(|err| StoreError::Backend(err.into()))?
+        .map_err(StoreError::from)
+    }
+
+    fn encode(&self, statement: &Registered<G>) -> Row {
+        (
+            statement.protocol().to_string(),
+            statement.to_proto(&*self.group).encode_to_vec(),
+        )
+    }
+
+    fn decode(&self, (protocol, statement): Row) -> Result<Registered<G>, StoreError> {
+        let protocol = protocol
+            .parse()
+            .map_err(|err: sigma::UnknownProtocol| StoreError::Unusable(err.to_string()))?;
+        Ok(Registered::from_proto(
+            &*self.group,
+            protocol,
+            decode_proto(&statement)?,
+        )?)
+    }
+}
+
+fn migrate(connection: &mut Connection) -> Result<(), StoreError> {
+    let version: usize = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version > MIGRATIONS.len() {
+        return Err(StoreError::Unusable(format!(
+            "schema version {} is newer than this server's {}",
+            version,
+            MIGRATIONS.len()
+        )));
+    }
+
+    for (applied, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.pragma_update(None, "user_version", applied + 1)?;
+        transaction.commit()?;
+    }
+
+    Ok(())
+}
+
+fn decode_proto<M: prost::Message + Default>(bytes: &[u8]) -> Result<M, StoreError> {
+    M::decode(bytes).map_err(|err| StoreError::Unusable(err.to_string()))
+}
+
+#[async_trait]
+impl<G: Group> UserStore<G> for SqliteStore<G> {
+    async fn register(&self, user: &str, data: UserData<G>) -> Result<bool, StoreError> {
+        let user = user.to_string();
+        let (protocol, statement) = self.encode(&data.credentials.statement);
+        let salt = data.credentials.salt;
+        let additional: Vec<_> = data
+            .additional
+            .into_iter()
+            .map(|credentials| (self.encode(&credentials.statement), credentials.salt))
+            .collect();
+
+        self.run(move |connection| {
+            let transaction = connection.transaction()?;
+            let inserted = transaction.execute(
+                "INSERT INTO users (name, protocol, statement, salt) VALUES (?1, ?2, ?3, ?4)
+                    ON CONFLICT (name) DO NOTHING",
+                params![user, protocol, statement, salt],
+            )?;
+            if inserted == 0 {
+                return Ok(false);
+            }
+
+            for (position, ((protocol, statement), salt)) in additional.into_iter().enumerate() {
+                transaction.execute(
+                    "INSERT INTO credentials (user, position, protocol, statement, salt)
+                        VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![user, position, protocol, statement, salt],
+                )?;
+            }
+            transaction.commit()?;
+
+            Ok(true)
+        })
+        .await
+    }
+
+    async fn user(&self, user: &str) -> Result<Option<UserData<G>>, StoreError> {
+        let user = user.to_string();
+        let rows = self
+            .run(move |connection| {
+                let transaction = connection.transaction()?;
+                let Some(primary) = transaction
+                    .query_row(
+                        "SELECT protocol, statement, salt FROM users WHERE name = ?1",
+                        params![user],
+                        |row| Ok(((row.get(0)?, row.get(1)?), row.get(2)?)),
+                    )
+                    .optional()?
+                else {
+                    return Ok(None);
+                };
+
+                let additional = transaction
+                    .prepare(
+                        "SELECT protocol, statement, salt FROM credentials
+                            WHERE user = ?1 ORDER BY position",
+                    )?
+                    .query_map(params![user], |row| {
+                        Ok(((row.get(0)?, row.get(1)?), row.get(2)?))
+                    })?
+                    .collect::<rusqlite::Result<Vec<(Row, Vec<u8>)>>>()?;
+
+                Ok(Some((primary, additional)))
+            })
+            .await?;
+
+        let Some((primary, additional)) = rows else {
+            return Ok(None);
+        };
+        let credentials = |(statement, salt): (Row, Vec<u8>)| {
+            Ok(Credentials {
+                statement: self.decode(statement)?,
+                salt,
+            })
+        };
+
+        Ok(Some(UserData {
+            credentials: credentials(primary)?,
+            additional: additional
+                .into_iter()
+                .map(credentials)
+                .collect::<Result<_, StoreError>>()?,
+        }))
+    }
+
+    async fn begin_challenge(&self, auth_id: &str, issued: Issued<G>) -> Result<(), StoreError> {
+        let (auth_id, user, expires) = (auth_id.to_string(), issued.user, issued.expires);
+        let (commitment, challenge) = issued.verifier.to_proto(&*self.group);
+        let (commitment, challenge) = (commitment.encode_to_vec(), challenge.encode_to_vec());
+
+        // The user's unanswered challenge, if any, conflicts on `user` and is replaced
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT OR REPLACE INTO challenges (auth_id, user, commitment, challenge, expires)
+                    VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![auth_id, user, commitment, challenge, expires],
+            )?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn take_challenge(&self, auth_id: &str) -> Result<Option<Issued<G>>, StoreError> {
+        let auth_id = auth_id.to_string();
+        let row = self
+            .run(move |connection| {
+                let transaction = connection.transaction()?;
+                let row: Option<(UserName, Row, Vec<u8>, Vec<u8>, u64)> = transaction
+                    .query_row(
+                        "SELECT challenges.user, users.protocol, users.statement,
+                                challenges.commitment, challenges.challenge, challenges.expires
+                            FROM challenges JOIN users ON users.name = challenges.user
+                            WHERE challenges.auth_id = ?1",
+                        params![auth_id],
+                        |row| {
+                            Ok((
+                                row.get(0)?,
+                                (row.get(1)?, row.get(2)?),
+                                row.get(3)?,
+                                row.get(4)?,
+                                row.get(5)?,
+                            ))
+                        },
+                    )
+                    .optional()?;
+                transaction.execute(
+                    "DELETE FROM challenges WHERE auth_id = ?1",
+                    params![auth_id],
+                )?;
+                transaction.commit()?;
+
+                Ok(row)
+            })
+            .await?;
+
+        let Some((user, statement, commitment, challenge, expires)) = row else {
+            return Ok(None);
+        };
+        let verifier = Challenged::from_proto(
+            &*self.group,
+            self.decode(statement)?,
+            decode_proto(&commitment)?,
+            decode_proto(&challenge)?,
+        )?;
+
+        Ok(Some(Issued {
+            user,
+            verifier,
+            expires,
+        }))
+    }
+
+    async fn reap_challenges(&self, now: u64) -> Result<usize, StoreError> {
+        self.run(move |connection| {
+            connection.execute("DELETE FROM challenges WHERE expires < ?1", params![now])
+        })
+        .await
+    }
+
+    async fn start_session(&self, user: &str, session_id: &str) -> Result<(), StoreError> {
+        let (user, session_id) = (user.to_string(), session_id.to_string());
+
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT OR REPLACE INTO sessions (user, session_id) VALUES (?1, ?2)",
+                params![user, session_id],
+            )?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn session(&self, user: &str) -> Result<Option<String>, StoreError> {
+        let user = user.to_string();
+
+        self.run(move |connection| {
+            connection
+                .query_row(
+                    "SELECT session_id FROM sessions WHERE user = ?1",
+                    params![user],
+                    |row| row.get(0),
+                )
+                .optional()
+        })
+        .await
+    }
+
+    async fn start_anonymous_session(&self, token: &str) -> Result<(), StoreError> {
+        let token = token.to_string();
+
+        self.run(move |connection| {
+            connection.execute(
+                "INSERT INTO anonymous_sessions (token) VALUES (?1)",
+                params![token],
+            )?;
+
+            Ok(())
+        })
+        .await
+    }
+}
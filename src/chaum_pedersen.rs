@@ -0,0 +1,212 @@
+use rand_core::{CryptoRng, RngCore};
+
+use crate::group::Group;
+use crate::secret::Secret;
+use crate::sigma;
+
+// y1 = G ^ x
+// y2 = H ^ x
+// x is a secret or a nonce, so this takes the constant-time path
+pub fn obfuscate<G: Group>(group: &G, x: &G::Scalar) -> (G::Element, G::Element) {
+    let (g, h) = group.generators();
+
+    (group.exp_secret(g, x), group.exp_secret(h, x))
+}
+
+// s = (k - (c * x)) mod q, with constant-time scalar arithmetic
+pub fn solve_challenge<G: Group>(
+    group: &G,
+    k: &G::Scalar,
+    c: &G::Scalar,
+    x: &G::Scalar,
+) -> G::Scalar {
+    let cx = Secret::new(group.scalar_mul(c, x));
+
+    group.scalar_sub(k, cx.expose())
+}
+
+// r1 ⇔ v1 = (G ^ s) · (y1 ^ c)
+// r2 ⇔ v2 = (H ^ s) · (y2 ^ c)
+// Elements are trusted to have come through `Group::decode_element`, but scalars are range checked here
+pub fn verify<G: Group>(
+    group: &G,
+    (y1, y2): (&G::Element, &G::Element),
+    (r1, r2): (&G::Element, &G::Element),
+    c: &G::Scalar,
+    s: &G::Scalar,
+) -> bool {
+    if !group.contains_scalar(c) || !group.contains_scalar(s) {
+        return false;
+    }
+
+    let (g, h) = group.generators();
+
+    let v1 = group.multi_exp((g, s), (y1, c));
+    let v2 = group.multi_exp((h, s), (y2, c));
+
+    r1 == &v1 && r2 == &v2
+}
+
+// r1 = (G ^ s) · (y1 ^ c)
+// r2 = (H ^ s) · (y2 ^ c)
+// for a random s: an accepting transcript for the challenge c, made without the secret. That
+// these are distributed exactly like real transcripts is what makes the protocol honest-verifier
+// zero-knowledge. Nothing here is secret, so this takes the variable-time path.
+pub fn simulate<G: Group, R: RngCore + CryptoRng>(
+    group: &G,
+    (y1, y2): (&G::Element, &G::Element),
+    c: &G::Scalar,
+    rng: &mut R,
+) -> ((G::Element, G::Element), G::Scalar) {
+    let (g, h) = group.generators();
+    let s = group.random_scalar(rng);
+
+    let r1 = group.multi_exp((g, &s), (y1, c));
+    let r2 = group.multi_exp((h, &s), (y2, c));
+
+    ((r1, r2), s)
+}
+
+/// ((y1, y2), (r1, r2), c, s), one transcript as `verify` takes it
+pub type BatchEntry<'a, G> = (
+    (&'a <G as Group>::Element, &'a <G as Group>::Element),
+    (&'a <G as Group>::Element, &'a <G as Group>::Element),
+    &'a <G as Group>::Scalar,
+    &'a <G as Group>::Scalar,
+);
+
+// verify(...) for every entry at about the cost of a single multi-exponentiation, returning the
+// (sorted) indices of the entries that fail.
+// A batch that doesn't hold is split in half until the failures are isolated, so a few bad
+// entries among many good ones cost a few extra batches rather than verifying each on its own.
+pub fn verify_batch<G: Group, R: RngCore + CryptoRng>(
+    group: &G,
+    entries: &[BatchEntry<'_, G>],
+    rng: &mut R,
+) -> Result<(), Vec<usize>> {
+    let mut failed = Vec::new();
+    let mut candidates = Vec::with_capacity(entries.len());
+    for (index, (_, _, c, s)) in entries.iter().enumerate() {
+        if group.contains_scalar(c) && group.contains_scalar(s) {
+            candidates.push(index);
+        } else {
+            failed.push(index);
+        }
+    }
+
+    bisect(group, entries, &candidates, rng, &mut failed);
+
+    if failed.is_empty() {
+        Ok(())
+    } else {
+        failed.sort_unstable();
+        Err(failed)
+    }
+}
+
+fn bisect<G: Group, R: RngCore + CryptoRng>(
+    group: &G,
+    entries: &[BatchEntry<'_, G>],
+    indices: &[usize],
+    rng: &mut R,
+    failed: &mut Vec<usize>,
+) {
+    match indices {
+        [] => {}
+        &[index] => {
+            let (y, r, c, s) = entries[index];
+            if !verify(group, y, r, c, s) {
+                failed.push(index);
+            }
+        }
+        _ if batch_holds(group, entries, indices, rng) => {}
+        _ => {
+            let (left, right) = indices.split_at(indices.len() / 2);
+            bisect(group, entries, left, rng, failed);
+            bisect(group, entries, right, rng, failed);
+        }
+    }
+}
+
+// Every entry's two equations, each raised to its own fresh random 128-bit weight, multiplied together:
+// ∏ r1^ρ · r2^σ = G^(Σ ρ·s) · H^(Σ σ·s) · ∏ y1^(ρ·c) · y2^(σ·c)
+// A batch containing a false equation passes with probability at most 2^-127, as long as every
+// element lies in the prime-order group, which `Group::decode_element` guarantees.
+fn batch_holds<G: Group, R: RngCore + CryptoRng>(
+    group: &G,
+    entries: &[BatchEntry<'_, G>],
+    indices: &[usize],
+    rng: &mut R,
+) -> bool {
+    let mut weight = || {
+        let n = (rng.next_u64() as u128) << 64 | rng.next_u64() as u128;
+        group.scalar_from_u128(n | 1)
+    };
+
+    let zero = group.scalar_from_u128(0);
+    let (mut g_exp, mut h_exp) = (zero.clone(), zero);
+    let mut commitments = Vec::with_capacity(2 * indices.len());
+    let mut statements = Vec::with_capacity(2 * indices.len());
+    for &index in indices {
+        let ((y1, y2), (r1, r2), c, s) = entries[index];
+        let (rho, sigma) = (weight(), weight());
+
+        g_exp = group.scalar_add(&g_exp, &group.scalar_mul(&rho, s));
+        h_exp = group.scalar_add(&h_exp, &group.scalar_mul(&sigma, s));
+        statements.push((y1, group.scalar_mul(&rho, c)));
+        statements.push((y2, group.scalar_mul(&sigma, c)));
+        commitments.push((r1, rho));
+        commitments.push((r2, sigma));
+    }
+
+    let (g, h) = group.generators();
+    let lhs: Vec<_> = commitments.iter().map(|(r, w)| (*r, w)).collect();
+    let rhs: Vec<_> = [(g, &g_exp), (h, &h_exp)]
+        .into_iter()
+        .chain(statements.iter().map(|(y, e)| (*y, e)))
+        .collect();
+
+    group.multi_exp_n(&lhs) == group.multi_exp_n(&rhs)
+}
+
+// c = SHA-512(group, y1, y2, r1, r2, user, context) mod q
+pub fn challenge<G: Group>(
+    group: &G,
+    (y1, y2): (&G::Element, &G::Element),
+    (r1, r2): (&G::Element, &G::Element),
+    user: &str,
+    context: &[u8],
+) -> G::Scalar {
+    sigma::transcript_challenge(group, None, &[y1, y2, r1, r2], user, context)
+}
+
+// (r1, r2) = obfuscate(k), c = challenge(...), s = solve_challenge(k, c, x)
+pub fn prove<G: Group>(
+    group: &G,
+    x: &G::Scalar,
+    k: &G::Scalar,
+    user: &str,
+    context: &[u8],
+) -> ((G::Element, G::Element), G::Scalar) {
+    let (y1, y2) = obfuscate(group, x);
+    let (r1, r2) = obfuscate(group, k);
+
+    let c = challenge(group, (&y1, &y2), (&r1, &r2), user, context);
+    let s = solve_challenge(group, k, &c, x);
+
+    ((r1, r2), s)
+}
+
+// verify(...) with c = challenge(...)
+pub fn verify_non_interactive<G: Group>(
+    group: &G,
+    (y1, y2): (&G::Element, &G::Element),
+    (r1, r2): (&G::Element, &G::Element),
+    s: &G::Scalar,
+    user: &str,
+    context: &[u8],
+) -> bool {
+    let c = challenge(group, (y1, y2), (r1, r2), user, context);
+
+    verify(group, (y1, y2), (r1, r2), &c, s)
+}
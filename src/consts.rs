@@ -0,0 +1,167 @@
+use lazy_static::lazy_static;
+use num_bigint::BigUint;
+
+#[cfg(feature = "ristretto")]
+use crate::group::Ristretto255;
+use crate::group::{Group, GroupId};
+use crate::Parameters;
+
+/// Public seed that `PARAMS.H` is hashed from
+pub const PARAMS_H_SEED: &[u8] = b"zkp-auth/modp2048/H";
+
+lazy_static! {
+    pub static ref PARAMS: Parameters = Parameters {
+        // Chosen from Internet Engineering Task Force RFC 3526: https://datatracker.ietf.org/doc/rfc3526
+        // === 2048-bit MODP group ===
+        G: BigUint::from(2_u8),
+        // Flipped to be compatible with BigUint::new()'s endianness
+        P: BigUint::new(vec![
+            0xFFFFFFFF, 0xFFFFFFFF, 0x8AACAA68, 0x15728E5A, 0x98FA0510, 0x15D22618, 0xEA956AE5, 0x3995497C,
+            0x95581718, 0xDE2BCBF6, 0x6F4C52C9, 0xB5C55DF0, 0xEC07A28F, 0x9B2783A2, 0x180E8603, 0xE39E772C,
+            0x2E36CE3B, 0x32905E46, 0xCA18217C, 0xF1746C08, 0x4ABC9804, 0x670C354E, 0x7096966D, 0x9ED52907,
+            0x208552BB, 0x1C62F356, 0xDCA3AD96, 0x83655D23, 0xFD24CF5F, 0x69163FA8, 0x1C55D39A, 0x98DA4836,
+            0xA163BF05, 0xC2007CB8, 0xECE45B3D, 0x49286651, 0x7C4B1FE6, 0xAE9F2411, 0x5A899FA5, 0xEE386BFB,
+            0xF406B7ED, 0x0BFF5CB6, 0xA637ED6B, 0xF44C42E9, 0x625E7EC6, 0xE485B576, 0x6D51C245, 0x4FE1356D,
+            0xF25F1437, 0x302B0A6D, 0xCD3A431B, 0xEF9519B3, 0x8E3404DD, 0x514A0879, 0x3B139B22, 0x020BBEA6,
+            0x8A67CC74, 0x29024E08, 0x80DC1CD1, 0xC4C6628B, 0x2168C234, 0xC90FDAA2, 0xFFFFFFFF, 0xFFFFFFFF,
+        ]),
+        // === 2048-bit MODP group ===
+
+        // q where q = (p - 1) / 2 and q is a prime
+        Q: BigUint::new(vec![
+            0xFFFFFFFF, 0x7FFFFFFF, 0x45565534, 0x0AB9472D, 0x4C7D0288, 0x8AE9130C, 0x754AB572, 0x1CCAA4BE,
+            0x4AAC0B8C, 0xEF15E5FB, 0x37A62964, 0xDAE2AEF8, 0x7603D147, 0xCD93C1D1, 0x0C074301, 0xF1CF3B96,
+            0x171B671D, 0x19482F23, 0x650C10BE, 0x78BA3604, 0x255E4C02, 0xB3861AA7, 0xB84B4B36, 0xCF6A9483,
+            0x1042A95D, 0x0E3179AB, 0xEE51D6CB, 0xC1B2AE91, 0x7E9267AF, 0x348B1FD4, 0x0E2AE9CD, 0xCC6D241B,
+            0x50B1DF82, 0xE1003E5C, 0xF6722D9E, 0x24943328, 0xBE258FF3, 0xD74F9208, 0xAD44CFD2, 0xF71C35FD,
+            0x7A035BF6, 0x85FFAE5B, 0xD31BF6B5, 0x7A262174, 0x312F3F63, 0xF242DABB, 0xB6A8E122, 0xA7F09AB6,
+            0xF92F8A1B, 0x98158536, 0xE69D218D, 0xF7CA8CD9, 0xC71A026E, 0x28A5043C, 0x1D89CD91, 0x0105DF53,
+            0x4533E63A, 0x94812704, 0xC06E0E68, 0x62633145, 0x10B4611A, 0xE487ED51, 0xFFFFFFFF, 0x7FFFFFFF,
+        ]),
+
+        // h = hash_to_element(PARAMS_H_SEED), see `group::Group::hash_to_element`
+        // Nobody knows log_G(h); recompute it with `consts::PARAMS.derives_h_from(consts::PARAMS_H_SEED)`
+        H: BigUint::new(vec![
+            0x634045DD, 0xF55301D9, 0xD45A136A, 0x77D5E0CA, 0x7B4CD35E, 0x6D14D58D, 0x086BB563, 0x4AA2A93E,
+            0xD407830C, 0x935B734A, 0xDD7B67DE, 0x22D7C4D4, 0x5CCC0DDA, 0x0A9531C3, 0xB6DBF7D8, 0xACE09645,
+            0x02441744, 0x3F95CF67, 0xAB196429, 0x6F9E27F6, 0xA8166F70, 0x59EB599E, 0xC182D352, 0x9A9D5EB4,
+            0x46BB5162, 0x5E224076, 0x50112990, 0xA6D5F3E5, 0xFEF18219, 0x0CE3EE0B, 0xDCA356BE, 0xD7ABBDAA,
+            0x76BE9FCB, 0x9976EBC5, 0x768AF17E, 0x9F672697, 0x11C3DAE4, 0x6BDE67B4, 0xEC94F92C, 0x47D080E4,
+            0x23139EB1, 0xDF3A8130, 0x549ACF2E, 0x04265A2A, 0x6D691665, 0x9BBAF49C, 0x1DD7488A, 0x1A47B491,
+            0x3A2C15DE, 0x8C2238F6, 0x4350B64F, 0xDFA5FD4B, 0x6DBABAA8, 0x0B101563, 0x28A497A7, 0xA333A6FD,
+            0xA8BD8D23, 0xD64C61FB, 0x6D0F0170, 0x943DBC59, 0x24A45245, 0x20CD70CC, 0xFA4B5974, 0x43C61B84,
+        ]),
+
+        name: "modp2048",
+    };
+}
+
+// RFC 3526 §4: 3072-bit MODP group
+const MODP_3072_P: &str = "
+    FFFFFFFF FFFFFFFF C90FDAA2 2168C234 C4C6628B 80DC1CD1 29024E08 8A67CC74
+    020BBEA6 3B139B22 514A0879 8E3404DD EF9519B3 CD3A431B 302B0A6D F25F1437
+    4FE1356D 6D51C245 E485B576 625E7EC6 F44C42E9 A637ED6B 0BFF5CB6 F406B7ED
+    EE386BFB 5A899FA5 AE9F2411 7C4B1FE6 49286651 ECE45B3D C2007CB8 A163BF05
+    98DA4836 1C55D39A 69163FA8 FD24CF5F 83655D23 DCA3AD96 1C62F356 208552BB
+    9ED52907 7096966D 670C354E 4ABC9804 F1746C08 CA18217C 32905E46 2E36CE3B
+    E39E772C 180E8603 9B2783A2 EC07A28F B5C55DF0 6F4C52C9 DE2BCBF6 95581718
+    3995497C EA956AE5 15D22618 98FA0510 15728E5A 8AAAC42D AD33170D 04507A33
+    A85521AB DF1CBA64 ECFB8504 58DBEF0A 8AEA7157 5D060C7D B3970F85 A6E1E4C7
+    ABF5AE8C DB0933D7 1E8C94E0 4A25619D CEE3D226 1AD2EE6B F12FFA06 D98A0864
+    D8760273 3EC86A64 521F2B18 177B200C BBE11757 7A615D6C 770988C0 BAD946E2
+    08E24FA0 74E5AB31 43DB5BFC E0FD108E 4B82D120 A93AD2CA FFFFFFFF FFFFFFFF";
+
+// RFC 3526 §5: 4096-bit MODP group
+const MODP_4096_P: &str = "
+    FFFFFFFF FFFFFFFF C90FDAA2 2168C234 C4C6628B 80DC1CD1 29024E08 8A67CC74
+    020BBEA6 3B139B22 514A0879 8E3404DD EF9519B3 CD3A431B 302B0A6D F25F1437
+    4FE1356D 6D51C245 E485B576 625E7EC6 F44C42E9 A637ED6B 0BFF5CB6 F406B7ED
+    EE386BFB 5A899FA5 AE9F2411 7C4B1FE6 49286651 ECE45B3D C2007CB8 A163BF05
+    98DA4836 1C55D39A 69163FA8 FD24CF5F 83655D23 DCA3AD96 1C62F356 208552BB
+    9ED52907 7096966D 670C354E 4ABC9804 F1746C08 CA18217C 32905E46 2E36CE3B
+    E39E772C 180E8603 9B2783A2 EC07A28F B5C55DF0 6F4C52C9 DE2BCBF6 95581718
+    3995497C EA956AE5 15D22618 98FA0510 15728E5A 8AAAC42D AD33170D 04507A33
+    A85521AB DF1CBA64 ECFB8504 58DBEF0A 8AEA7157 5D060C7D B3970F85 A6E1E4C7
+    ABF5AE8C DB0933D7 1E8C94E0 4A25619D CEE3D226 1AD2EE6B F12FFA06 D98A0864
+    D8760273 3EC86A64 521F2B18 177B200C BBE11757 7A615D6C 770988C0 BAD946E2
+    08E24FA0 74E5AB31 43DB5BFC E0FD108E 4B82D120 A9210801 1A723C12 A787E6D7
+    88719A10 BDBA5B26 99C32718 6AF4E23C 1A946834 B6150BDA 2583E9CA 2AD44CE8
+    DBBBC2DB 04DE8EF9 2E8EFC14 1FBECAA6 287C5947 4E6BC05D 99B2964F A090C3A2
+    233BA186 515BE7ED 1F612970 CEE2D7AF B81BDD76 2170481C D0069127 D5B05AA9
+    93B4EA98 8D8FDDC1 86FFB7DC 90A6C08F 4DF435C9 34063199 FFFFFFFF FFFFFFFF";
+
+// RFC 7919 §A.1: ffdhe2048
+const FFDHE_2048_P: &str = "
+    FFFFFFFF FFFFFFFF ADF85458 A2BB4A9A AFDC5620 273D3CF1 D8B9C583 CE2D3695
+    A9E13641 146433FB CC939DCE 249B3EF9 7D2FE363 630C75D8 F681B202 AEC4617A
+    D3DF1ED5 D5FD6561 2433F51F 5F066ED0 85636555 3DED1AF3 B557135E 7F57C935
+    984F0C70 E0E68B77 E2A689DA F3EFE872 1DF158A1 36ADE735 30ACCA4F 483A797A
+    BC0AB182 B324FB61 D108A94B B2C8E3FB B96ADAB7 60D7F468 1D4F42A3 DE394DF4
+    AE56EDE7 6372BB19 0B07A7C8 EE0A6D70 9E02FCE1 CDF7E2EC C03404CD 28342F61
+    9172FE9C E98583FF 8E4F1232 EEF28183 C3FE3B1B 4C6FAD73 3BB5FCBC 2EC22005
+    C58EF183 7D1683B2 C6F34A26 C1B2EFFA 886B4238 61285C97 FFFFFFFF FFFFFFFF";
+
+// RFC 7919 §A.2: ffdhe3072
+const FFDHE_3072_P: &str = "
+    FFFFFFFF FFFFFFFF ADF85458 A2BB4A9A AFDC5620 273D3CF1 D8B9C583 CE2D3695
+    A9E13641 146433FB CC939DCE 249B3EF9 7D2FE363 630C75D8 F681B202 AEC4617A
+    D3DF1ED5 D5FD6561 2433F51F 5F066ED0 85636555 3DED1AF3 B557135E 7F57C935
+    984F0C70 E0E68B77 E2A689DA F3EFE872 1DF158A1 36ADE735 30ACCA4F 483A797A
+    BC0AB182 B324FB61 D108A94B B2C8E3FB B96ADAB7 60D7F468 1D4F42A3 DE394DF4
+    AE56EDE7 6372BB19 0B07A7C8 EE0A6D70 9E02FCE1 CDF7E2EC C03404CD 28342F61
+    9172FE9C E98583FF 8E4F1232 EEF28183 C3FE3B1B 4C6FAD73 3BB5FCBC 2EC22005
+    C58EF183 7D1683B2 C6F34A26 C1B2EFFA 886B4238 611FCFDC DE355B3B 6519035B
+    BC34F4DE F99C0238 61B46FC9 D6E6C907 7AD91D26 91F7F7EE 598CB0FA C186D91C
+    AEFE1309 85139270 B4130C93 BC437944 F4FD4452 E2D74DD3 64F2E21E 71F54BFF
+    5CAE82AB 9C9DF69E E86D2BC5 22363A0D ABC52197 9B0DEADA 1DBF9A42 D5C4484E
+    0ABCD06B FA53DDEF 3C1B20EE 3FD59D7C 25E41D2B 66C62E37 FFFFFFFF FFFFFFFF";
+
+// RFC 7919 §A.3: ffdhe4096
+const FFDHE_4096_P: &str = "
+    FFFFFFFF FFFFFFFF ADF85458 A2BB4A9A AFDC5620 273D3CF1 D8B9C583 CE2D3695
+    A9E13641 146433FB CC939DCE 249B3EF9 7D2FE363 630C75D8 F681B202 AEC4617A
+    D3DF1ED5 D5FD6561 2433F51F 5F066ED0 85636555 3DED1AF3 B557135E 7F57C935
+    984F0C70 E0E68B77 E2A689DA F3EFE872 1DF158A1 36ADE735 30ACCA4F 483A797A
+    BC0AB182 B324FB61 D108A94B B2C8E3FB B96ADAB7 60D7F468 1D4F42A3 DE394DF4
+    AE56EDE7 6372BB19 0B07A7C8 EE0A6D70 9E02FCE1 CDF7E2EC C03404CD 28342F61
+    9172FE9C E98583FF 8E4F1232 EEF28183 C3FE3B1B 4C6FAD73 3BB5FCBC 2EC22005
+    C58EF183 7D1683B2 C6F34A26 C1B2EFFA 886B4238 611FCFDC DE355B3B 6519035B
+    BC34F4DE F99C0238 61B46FC9 D6E6C907 7AD91D26 91F7F7EE 598CB0FA C186D91C
+    AEFE1309 85139270 B4130C93 BC437944 F4FD4452 E2D74DD3 64F2E21E 71F54BFF
+    5CAE82AB 9C9DF69E E86D2BC5 22363A0D ABC52197 9B0DEADA 1DBF9A42 D5C4484E
+    0ABCD06B FA53DDEF 3C1B20EE 3FD59D7C 25E41D2B 669E1EF1 6E6F52C3 164DF4FB
+    7930E9E4 E58857B6 AC7D5F42 D69F6D18 7763CF1D 55034004 87F55BA5 7E31CC7A
+    7135C886 EFB4318A ED6A1E01 2D9E6832 A907600A 918130C4 6DC778F9 71AD0038
+    092999A3 33CB8B7A 1A1DB93D 7140003C 2A4ECEA9 F98D0ACC 0A8291CD CEC97DCF
+    8EC9B55A 7F88A46B 4DB5A851 F44182E1 C68A007E 5E655F6A FFFFFFFF FFFFFFFF";
+
+lazy_static! {
+    pub static ref MODP_3072: Parameters = safe_prime_group(GroupId::Modp3072, MODP_3072_P);
+    pub static ref MODP_4096: Parameters = safe_prime_group(GroupId::Modp4096, MODP_4096_P);
+    pub static ref FFDHE_2048: Parameters = safe_prime_group(GroupId::Ffdhe2048, FFDHE_2048_P);
+    pub static ref FFDHE_3072: Parameters = safe_prime_group(GroupId::Ffdhe3072, FFDHE_3072_P);
+    pub static ref FFDHE_4096: Parameters = safe_prime_group(GroupId::Ffdhe4096, FFDHE_4096_P);
+}
+
+#[cfg(feature = "ristretto")]
+lazy_static! {
+    pub static ref RISTRETTO255: Ristretto255 = Ristretto255::new();
+}
+
+// G = 2, Q = (P - 1) / 2, H = hash_to_element("zkp-auth/<id>/H")
+// Every group we ship is a safe prime whose generator 2 lies in the order-Q subgroup
+#[allow(non_snake_case)]
+fn safe_prime_group(id: GroupId, p: &str) -> Parameters {
+    let digits: String = p.split_whitespace().collect();
+    let P = BigUint::parse_bytes(digits.as_bytes(), 16).expect("malformed prime");
+
+    let mut params = Parameters {
+        G: BigUint::from(2_u8),
+        Q: (&P - 1_u8) >> 1,
+        H: BigUint::from(2_u8),
+        P,
+        name: id.as_str(),
+    };
+    params.H = params.hash_to_element(&id.h_seed());
+    params
+}
@@ -0,0 +1,44 @@
+/// The version of the protocol, bumped whenever what a proof means changes
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The deployment a proof is made for: which server, in which realm, under which version of
+/// the protocol
+///
+/// Every challenge, interactive or not, is derived from [`Context::bind`], so a proof made for
+/// one deployment or account means nothing to another.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Context {
+    /// The server's identity, as its clients know it
+    pub server: String,
+    /// The realm of accounts on that server
+    pub realm: String,
+    pub version: u32,
+}
+
+impl Context {
+    pub fn new(server: impl Into<String>, realm: impl Into<String>) -> Self {
+        Self {
+            server: server.into(),
+            realm: realm.into(),
+            version: PROTOCOL_VERSION,
+        }
+    }
+
+    /// The context a proof by `user` for `purpose` (such as `login_context(timestamp)`) is bound to
+    ///
+    /// Every field is length-prefixed so that no two contexts are the same bytes.
+    pub fn bind(&self, user: &str, purpose: &[u8]) -> Vec<u8> {
+        let mut context = self.version.to_be_bytes().to_vec();
+        for field in [
+            self.server.as_bytes(),
+            self.realm.as_bytes(),
+            user.as_bytes(),
+            purpose,
+        ] {
+            context.extend_from_slice(&(field.len() as u64).to_be_bytes());
+            context.extend_from_slice(field);
+        }
+
+        context
+    }
+}
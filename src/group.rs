@@ -0,0 +1,315 @@
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
+
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::secret::Wipe;
+use crate::{consts, ParameterError, Parameters};
+
+mod modp;
+mod precomputed;
+#[cfg(feature = "ristretto")]
+mod ristretto;
+
+pub use modp::FixedBaseTable;
+pub use precomputed::Precomputed;
+#[cfg(feature = "ristretto")]
+pub use ristretto::Ristretto255;
+
+/// A prime-order group with two independent generators `G` and `H`
+///
+/// Everything the Chaum-Pedersen protocol needs is expressed in terms of this trait,
+/// so the same protocol code runs over any backend.
+pub trait Group: Send + Sync + 'static {
+    type Element: Clone + PartialEq + Debug + Send + Sync;
+    type Scalar: Clone + PartialEq + Debug + Send + Sync + Wipe;
+    /// Precomputed powers of one fixed base, see [`Group::precompute`]
+    type Table: Send + Sync;
+
+    /// Stable identifier for this group
+    fn name(&self) -> &'static str;
+
+    /// The generators `(G, H)`
+    fn generators(&self) -> (&Self::Element, &Self::Element);
+
+    /// The canonical encoding of every public parameter describing this group:
+    /// `[G, H, P, Q]` for MODP groups and `[G, H]` for elliptic-curve groups
+    fn parameters(&self) -> Vec<Vec<u8>>;
+
+    /// SHA-256 over the group's name and length-prefixed parameters, suitable for pinning
+    fn fingerprint(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for field in [self.name().as_bytes().to_vec()]
+            .into_iter()
+            .chain(self.parameters())
+        {
+            hasher.update((field.len() as u64).to_be_bytes());
+            hasher.update(field);
+        }
+        hasher.finalize().into()
+    }
+
+    /// Check that the parameters describe a sound prime-order group with independent generators
+    fn validate(&self) -> Result<(), ParameterError>;
+
+    /// base ^ exp
+    ///
+    /// Not necessarily constant-time: use [`Group::exp_secret`] when `exp` is secret.
+    fn exp(&self, base: &Self::Element, exp: &Self::Scalar) -> Self::Element;
+
+    /// base ^ exp, in time independent of the value of `exp`
+    fn exp_secret(&self, base: &Self::Element, exp: &Self::Scalar) -> Self::Element;
+
+    /// a · b
+    fn mul(&self, a: &Self::Element, b: &Self::Element) -> Self::Element;
+
+    /// The neutral element, which never appears in an honest transcript
+    fn identity(&self) -> Self::Element;
+
+    /// (a ^ x) · (b ^ y)
+    ///
+    /// Backends override this with simultaneous multi-exponentiation. It may run in
+    /// variable time, so it is only meant for public values such as those in `verify`.
+    fn multi_exp(
+        &self,
+        (a, x): (&Self::Element, &Self::Scalar),
+        (b, y): (&Self::Element, &Self::Scalar),
+    ) -> Self::Element {
+        self.mul(&self.exp(a, x), &self.exp(b, y))
+    }
+
+    /// ∏ base_i ^ exp_i over any number of terms
+    ///
+    /// Backends override this with an interleaved multi-exponentiation, which shares one chain
+    /// of squarings across every term. Like [`Group::multi_exp`], it may run in variable time.
+    fn multi_exp_n(&self, terms: &[(&Self::Element, &Self::Scalar)]) -> Self::Element {
+        terms.iter().fold(self.identity(), |acc, (base, exp)| {
+            self.mul(&acc, &self.exp(base, exp))
+        })
+    }
+
+    /// Build a table that makes repeated exponentiation of `base`, an element of the group, cheaper
+    fn precompute(&self, base: &Self::Element) -> Self::Table;
+
+    /// base ^ exp, for the `base` that `table` was built from, in time independent of the value of `exp`
+    fn exp_precomputed(&self, table: &Self::Table, exp: &Self::Scalar) -> Self::Element;
+
+    /// (a ^ x) · (b ^ y), for the `a` that `table` was built from
+    ///
+    /// Like [`Group::multi_exp`], this may run in variable time.
+    fn multi_exp_precomputed(
+        &self,
+        (table, x): (&Self::Table, &Self::Scalar),
+        (b, y): (&Self::Element, &Self::Scalar),
+    ) -> Self::Element {
+        self.mul(&self.exp_precomputed(table, x), &self.exp(b, y))
+    }
+
+    // Scalar arithmetic handles the secret `x` and nonce `k`, so it must run in time
+    // independent of the values of its operands
+
+    /// (a + b) mod q
+    fn scalar_add(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    /// (a - b) mod q
+    fn scalar_sub(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    /// (a * b) mod q
+    fn scalar_mul(&self, a: &Self::Scalar, b: &Self::Scalar) -> Self::Scalar;
+
+    /// The scalar `n`, for small values such as batching weights
+    fn scalar_from_u128(&self, n: u128) -> Self::Scalar;
+
+    /// Reduce a uniformly random byte string (at least 64 bytes) into a scalar
+    fn scalar_from_wide(&self, bytes: &[u8]) -> Self::Scalar;
+
+    /// A uniformly random scalar
+    fn random_scalar<R: RngCore + CryptoRng>(&self, rng: &mut R) -> Self::Scalar;
+
+    /// Map arbitrary data onto an element of the group with unknown discrete log
+    fn hash_to_element(&self, data: &[u8]) -> Self::Element;
+
+    /// Whether `H` is exactly `hash_to_element(seed)`, i.e. it was generated with nothing up our sleeve
+    fn derives_h_from(&self, seed: &[u8]) -> bool {
+        &self.hash_to_element(seed) == self.generators().1
+    }
+
+    /// Whether `element` lies in the prime-order group and is not the identity
+    ///
+    /// The identity never shows up in an honest transcript, and accepting it (or anything
+    /// outside the prime-order group) lets a malicious party force degenerate proofs.
+    fn contains_element(&self, element: &Self::Element) -> bool;
+
+    /// Whether `scalar` is fully reduced mod q
+    fn contains_scalar(&self, scalar: &Self::Scalar) -> bool;
+
+    /// Length in bytes of every encoded element
+    fn element_len(&self) -> usize;
+
+    /// Length in bytes of every encoded scalar
+    fn scalar_len(&self) -> usize;
+
+    /// The canonical encoding of `element`, exactly [`Group::element_len`] bytes long
+    fn encode_element(&self, element: &Self::Element) -> Vec<u8>;
+
+    /// Decode an element, rejecting anything that isn't [`Group::element_len`] bytes long or fails [`Group::contains_element`]
+    fn decode_element(&self, bytes: &[u8]) -> Option<Self::Element>;
+
+    /// The canonical encoding of `scalar`, exactly [`Group::scalar_len`] bytes long
+    fn encode_scalar(&self, scalar: &Self::Scalar) -> Vec<u8>;
+
+    /// Decode a scalar, rejecting anything that isn't [`Group::scalar_len`] bytes long or fails [`Group::contains_scalar`]
+    fn decode_scalar(&self, bytes: &[u8]) -> Option<Self::Scalar>;
+}
+
+/// Stable identifiers for the groups that can be selected at runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupId {
+    /// RFC 3526 2048-bit MODP group
+    Modp2048,
+    /// RFC 3526 3072-bit MODP group
+    Modp3072,
+    /// RFC 3526 4096-bit MODP group
+    Modp4096,
+    /// RFC 7919 2048-bit finite field group
+    Ffdhe2048,
+    /// RFC 7919 3072-bit finite field group
+    Ffdhe3072,
+    /// RFC 7919 4096-bit finite field group
+    Ffdhe4096,
+    /// Ristretto255 over Curve25519
+    #[cfg(feature = "ristretto")]
+    Ristretto255,
+}
+
+/// A registered group resolved to its backend
+#[derive(Debug, Clone, Copy)]
+pub enum NamedGroup {
+    Modp(&'static Parameters),
+    #[cfg(feature = "ristretto")]
+    Ristretto255(&'static Ristretto255),
+}
+
+impl NamedGroup {
+    pub fn parameters(self) -> Vec<Vec<u8>> {
+        match self {
+            NamedGroup::Modp(group) => group.parameters(),
+            #[cfg(feature = "ristretto")]
+            NamedGroup::Ristretto255(group) => group.parameters(),
+        }
+    }
+
+    pub fn fingerprint(self) -> [u8; 32] {
+        match self {
+            NamedGroup::Modp(group) => group.fingerprint(),
+            #[cfg(feature = "ristretto")]
+            NamedGroup::Ristretto255(group) => group.fingerprint(),
+        }
+    }
+
+    pub fn validate(self) -> Result<(), ParameterError> {
+        match self {
+            NamedGroup::Modp(group) => group.validate(),
+            #[cfg(feature = "ristretto")]
+            NamedGroup::Ristretto255(group) => group.validate(),
+        }
+    }
+}
+
+impl GroupId {
+    pub const ALL: &'static [GroupId] = &[
+        GroupId::Modp2048,
+        GroupId::Modp3072,
+        GroupId::Modp4096,
+        GroupId::Ffdhe2048,
+        GroupId::Ffdhe3072,
+        GroupId::Ffdhe4096,
+        #[cfg(feature = "ristretto")]
+        GroupId::Ristretto255,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GroupId::Modp2048 => "modp2048",
+            GroupId::Modp3072 => "modp3072",
+            GroupId::Modp4096 => "modp4096",
+            GroupId::Ffdhe2048 => "ffdhe2048",
+            GroupId::Ffdhe3072 => "ffdhe3072",
+            GroupId::Ffdhe4096 => "ffdhe4096",
+            #[cfg(feature = "ristretto")]
+            GroupId::Ristretto255 => "ristretto255",
+        }
+    }
+
+    /// The public seed this group's `H` is hashed from: `zkp-auth/<id>/H`
+    pub fn h_seed(self) -> Vec<u8> {
+        format!("zkp-auth/{}/H", self).into_bytes()
+    }
+
+    pub fn resolve(self) -> NamedGroup {
+        match self {
+            GroupId::Modp2048 => NamedGroup::Modp(&consts::PARAMS),
+            GroupId::Modp3072 => NamedGroup::Modp(&consts::MODP_3072),
+            GroupId::Modp4096 => NamedGroup::Modp(&consts::MODP_4096),
+            GroupId::Ffdhe2048 => NamedGroup::Modp(&consts::FFDHE_2048),
+            GroupId::Ffdhe3072 => NamedGroup::Modp(&consts::FFDHE_3072),
+            GroupId::Ffdhe4096 => NamedGroup::Modp(&consts::FFDHE_4096),
+            #[cfg(feature = "ristretto")]
+            GroupId::Ristretto255 => NamedGroup::Ristretto255(&consts::RISTRETTO255),
+        }
+    }
+}
+
+impl Display for GroupId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownGroup(String);
+
+impl Display for UnknownGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown group '{}', expected one of: ", self.0)?;
+        for (i, id) in GroupId::ALL.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "'{}'", id)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownGroup {}
+
+impl FromStr for GroupId {
+    type Err = UnknownGroup;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        GroupId::ALL
+            .iter()
+            .find(|id| id.as_str() == s)
+            .copied()
+            .ok_or_else(|| UnknownGroup(s.to_string()))
+    }
+}
+
+/// SHA-512 in counter mode, stretched to `len` bytes
+pub(crate) fn expand(data: &[u8], len: usize) -> Vec<u8> {
+    let mut output = Vec::with_capacity(len + 64);
+    let mut counter = 0_u32;
+    while output.len() < len {
+        output.extend(
+            Sha512::new()
+                .chain_update(counter.to_be_bytes())
+                .chain_update(data)
+                .finalize(),
+        );
+        counter += 1;
+    }
+    output.truncate(len);
+    output
+}
@@ -0,0 +1,314 @@
+use num_bigint::BigUint;
+use num_traits::One;
+use rand_core::{CryptoRng, RngCore};
+use zeroize::Zeroizing;
+
+use super::{expand, Group};
+use crate::secret::Secret;
+use crate::{ParameterError, Parameters};
+
+mod montgomery;
+
+use montgomery::{from_limbs, limb_len, select, to_limbs, window, windows, Montgomery};
+
+// Extra bytes sampled beyond the modulus size so that reduction leaves a bias below 2^-128
+const REDUCTION_MARGIN: usize = 16;
+
+// Exponent bits consumed per step of a fixed-base table: 16 entries per window,
+// so about 2 MiB per generator of a 2048-bit group
+const TABLE_WINDOW: u32 = 4;
+
+// Exponent bits consumed per step of simultaneous exponentiation: a 16-entry table of a^i · b^j
+const SHAMIR_WINDOW: u32 = 2;
+
+// Exponent bits consumed per step of interleaved multi-exponentiation, with a 16-entry table per base
+const STRAUS_WINDOW: u32 = 4;
+
+/// Powers `base ^ (d · 2^(4i))` of one fixed base for every window `i` of a full-size
+/// exponent and every digit `d`, kept in Montgomery form
+///
+/// Exponentiation then needs one multiplication per window and no squarings at all.
+/// Every window is multiplied in and every lookup reads the whole row, so it runs in
+/// constant time and is safe to use with secret exponents.
+#[derive(Debug, Clone)]
+pub struct FixedBaseTable {
+    montgomery: Montgomery,
+    windows: Vec<Vec<Vec<u64>>>,
+}
+
+fn byte_len(n: &BigUint) -> usize {
+    n.bits().div_ceil(8) as usize
+}
+
+// Big-endian, left-padded with zeros to exactly `len` bytes
+fn encode_fixed(n: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = n.to_bytes_be();
+    let mut encoded = vec![0; len.saturating_sub(bytes.len())];
+    encoded.extend(bytes);
+    encoded
+}
+
+impl Group for Parameters {
+    type Element = BigUint;
+    type Scalar = BigUint;
+    type Table = FixedBaseTable;
+
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn generators(&self) -> (&BigUint, &BigUint) {
+        (&self.G, &self.H)
+    }
+
+    fn parameters(&self) -> Vec<Vec<u8>> {
+        [&self.G, &self.H, &self.P, &self.Q]
+            .into_iter()
+            .map(BigUint::to_bytes_be)
+            .collect()
+    }
+
+    fn validate(&self) -> Result<(), ParameterError> {
+        Parameters::validate(self)
+    }
+
+    fn exp(&self, base: &BigUint, exp: &BigUint) -> BigUint {
+        base.modpow(exp, &self.P)
+    }
+
+    fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        (a * b) % &self.P
+    }
+
+    fn identity(&self) -> BigUint {
+        BigUint::one()
+    }
+
+    // Shamir's trick: walk both exponents together, two bits at a time, so a single
+    // squaring chain serves both terms
+    fn multi_exp(&self, (a, x): (&BigUint, &BigUint), (b, y): (&BigUint, &BigUint)) -> BigUint {
+        let montgomery = Montgomery::new(&self.P);
+        let size = 1 << SHAMIR_WINDOW;
+
+        // table[i · size + j] = a^i · b^j
+        let (a, b) = (montgomery.encode(a), montgomery.encode(b));
+        let mut table = Vec::with_capacity(size * size);
+        let mut a_i = montgomery.one();
+        for _ in 0..size {
+            let mut entry = a_i.clone();
+            for _ in 0..size {
+                let next = montgomery.mul(&entry, &b);
+                table.push(entry);
+                entry = next;
+            }
+            a_i = montgomery.mul(&a_i, &a);
+        }
+
+        let len = limb_len(x).max(limb_len(y));
+        let (x, y) = (to_limbs(x, len), to_limbs(y, len));
+        let mut acc = montgomery.one();
+        for index in (0..windows(&x, SHAMIR_WINDOW)).rev() {
+            for _ in 0..SHAMIR_WINDOW {
+                acc = montgomery.mul(&acc, &acc);
+            }
+            let entry = window(&x, index, SHAMIR_WINDOW) * size + window(&y, index, SHAMIR_WINDOW);
+            if entry != 0 {
+                acc = montgomery.mul(&acc, &table[entry]);
+            }
+        }
+
+        montgomery.decode(&acc)
+    }
+
+    // Straus: one squaring chain as long as the longest exponent, with each term multiplying in
+    // its windows as they come up. Short exponents only pay for their own few windows.
+    fn multi_exp_n(&self, terms: &[(&BigUint, &BigUint)]) -> BigUint {
+        let montgomery = Montgomery::new(&self.P);
+
+        // Per term: table[d] = base^d, and the exponent at its own length
+        let terms: Vec<_> = terms
+            .iter()
+            .filter(|(_, exp)| limb_len(exp) > 0)
+            .map(|(base, exp)| {
+                let base = montgomery.encode(base);
+                let mut table = vec![montgomery.one()];
+                for d in 1..(1 << STRAUS_WINDOW) {
+                    table.push(montgomery.mul(&table[d - 1], &base));
+                }
+                (table, to_limbs(exp, limb_len(exp)))
+            })
+            .collect();
+
+        let len = terms.iter().map(|(_, exp)| exp.len()).max().unwrap_or(0);
+        let mut acc = montgomery.one();
+        for index in (0..len * (u64::BITS / STRAUS_WINDOW) as usize).rev() {
+            for _ in 0..STRAUS_WINDOW {
+                acc = montgomery.mul(&acc, &acc);
+            }
+            for (table, exp) in &terms {
+                if index < windows(exp, STRAUS_WINDOW) {
+                    let digit = window(exp, index, STRAUS_WINDOW);
+                    if digit != 0 {
+                        acc = montgomery.mul(&acc, &table[digit]);
+                    }
+                }
+            }
+        }
+
+        montgomery.decode(&acc)
+    }
+
+    fn precompute(&self, base: &BigUint) -> FixedBaseTable {
+        let montgomery = Montgomery::new(&self.P);
+        let windows = limb_len(&self.Q) * (u64::BITS / TABLE_WINDOW) as usize;
+
+        // Each window starts from base ^ (2^(4i)), which is the last window's 16th power
+        let mut power = montgomery.encode(base);
+        let windows = (0..windows)
+            .map(|_| {
+                let mut row = Vec::with_capacity(1 << TABLE_WINDOW);
+                let mut entry = montgomery.one();
+                for _ in 0..(1 << TABLE_WINDOW) {
+                    let next = montgomery.mul(&entry, &power);
+                    row.push(entry);
+                    entry = next;
+                }
+                power = entry;
+                row
+            })
+            .collect();
+
+        FixedBaseTable {
+            montgomery,
+            windows,
+        }
+    }
+
+    fn exp_precomputed(&self, table: &FixedBaseTable, exp: &BigUint) -> BigUint {
+        let FixedBaseTable {
+            montgomery,
+            windows,
+        } = table;
+        // Only reduced exponents fit the table; anything wider is reduced first since the base has order Q
+        let len = windows.len() / (u64::BITS / TABLE_WINDOW) as usize;
+        if limb_len(exp) > len {
+            return self.exp_precomputed(table, Secret::new(exp % &self.Q).expose());
+        }
+
+        let exp = Zeroizing::new(to_limbs(exp, len));
+        let mut acc = montgomery.one();
+        for (index, row) in windows.iter().enumerate() {
+            acc = montgomery.mul(&acc, &select(row, window(&exp, index, TABLE_WINDOW)));
+        }
+
+        montgomery.decode(&acc)
+    }
+
+    // Fixed windows over at least as many limbs as Q, so the time depends only on the group
+    fn exp_secret(&self, base: &BigUint, exp: &BigUint) -> BigUint {
+        let montgomery = Montgomery::new(&self.P);
+        let len = limb_len(&self.Q).max(limb_len(exp));
+
+        let exp = Zeroizing::new(to_limbs(exp, len));
+
+        montgomery.decode(&montgomery.pow(&montgomery.encode(base), &exp))
+    }
+
+    // Scalar arithmetic is in constant time over limbs mod Q, wiping them afterwards:
+    // `solve_challenge` feeds it the secret `x` and nonce `k`
+
+    fn scalar_add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let field = Montgomery::new(&self.Q);
+        let (a, b) = (
+            Zeroizing::new(field.limbs(a)),
+            Zeroizing::new(field.limbs(b)),
+        );
+        from_limbs(&Zeroizing::new(field.add(&a, &b)))
+    }
+
+    fn scalar_sub(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let field = Montgomery::new(&self.Q);
+        let (a, b) = (
+            Zeroizing::new(field.limbs(a)),
+            Zeroizing::new(field.limbs(b)),
+        );
+        from_limbs(&Zeroizing::new(field.sub(&a, &b)))
+    }
+
+    // (a · R) · b · R^-1 = a · b
+    fn scalar_mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let field = Montgomery::new(&self.Q);
+        let (a, b) = (
+            Zeroizing::new(field.encode(a)),
+            Zeroizing::new(field.limbs(b)),
+        );
+        from_limbs(&Zeroizing::new(field.mul(&a, &b)))
+    }
+
+    fn scalar_from_u128(&self, n: u128) -> BigUint {
+        BigUint::from(n) % &self.Q
+    }
+
+    // Callers pass in stretched passwords
+    fn scalar_from_wide(&self, bytes: &[u8]) -> BigUint {
+        Secret::new(BigUint::from_bytes_be(bytes)).expose() % &self.Q
+    }
+
+    // Uniform in [1, Q)
+    fn random_scalar<R: RngCore + CryptoRng>(&self, rng: &mut R) -> BigUint {
+        let mut bytes = Zeroizing::new(vec![0; byte_len(&self.Q) + REDUCTION_MARGIN]);
+        rng.fill_bytes(&mut bytes);
+
+        Secret::new(BigUint::from_bytes_be(&bytes)).expose() % (&self.Q - 1_u8) + 1_u8
+    }
+
+    // e = (expand(data) mod P) ^ 2 mod P
+    // Squaring lands in the subgroup of quadratic residues, which has order Q
+    fn hash_to_element(&self, data: &[u8]) -> BigUint {
+        let len = byte_len(&self.P) + REDUCTION_MARGIN;
+        for counter in 0_u32.. {
+            let seed = [data, &counter.to_be_bytes()].concat();
+            let e = (BigUint::from_bytes_be(&expand(&seed, len)) % &self.P)
+                .modpow(&2_u8.into(), &self.P);
+            if e > BigUint::one() {
+                return e;
+            }
+        }
+        unreachable!("ran out of hash-to-group attempts")
+    }
+
+    // 1 < e < P and e ^ Q mod P = 1
+    fn contains_element(&self, element: &BigUint) -> bool {
+        element > &BigUint::one() && element < &self.P && element.modpow(&self.Q, &self.P).is_one()
+    }
+
+    fn contains_scalar(&self, scalar: &BigUint) -> bool {
+        scalar < &self.Q
+    }
+
+    fn element_len(&self) -> usize {
+        byte_len(&self.P)
+    }
+
+    fn scalar_len(&self) -> usize {
+        byte_len(&self.Q)
+    }
+
+    fn encode_element(&self, element: &BigUint) -> Vec<u8> {
+        encode_fixed(element, self.element_len())
+    }
+
+    fn decode_element(&self, bytes: &[u8]) -> Option<BigUint> {
+        Some(BigUint::from_bytes_be(bytes))
+            .filter(|element| bytes.len() == self.element_len() && self.contains_element(element))
+    }
+
+    fn encode_scalar(&self, scalar: &BigUint) -> Vec<u8> {
+        encode_fixed(scalar, self.scalar_len())
+    }
+
+    fn decode_scalar(&self, bytes: &[u8]) -> Option<BigUint> {
+        Some(BigUint::from_bytes_be(bytes))
+            .filter(|scalar| bytes.len() == self.scalar_len() && self.contains_scalar(scalar))
+    }
+}
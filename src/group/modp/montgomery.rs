@@ -0,0 +1,229 @@
+use num_bigint::BigUint;
+use num_traits::One;
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::{Zeroize, Zeroizing};
+
+// Exponent bits consumed per step of `Montgomery::pow`
+const POW_WINDOW: u32 = 4;
+
+/// Montgomery arithmetic modulo an odd `N`, over fixed-width little-endian 64-bit limbs
+///
+/// Values are kept in Montgomery form `a · R mod N` with `R = 2^(64 · limbs)`, so a
+/// multiplication costs one interleaved multiply-and-reduce pass instead of a full division.
+///
+/// Everything operating on limbs runs in time that depends only on the modulus: no branches
+/// or memory accesses depend on the values. Converting from a `BigUint` can still reveal how
+/// many leading zero limbs it had, since `BigUint` doesn't store them.
+///
+/// Scratch space is wiped before it's released, since the operands may be secrets.
+#[derive(Debug, Clone)]
+pub(crate) struct Montgomery {
+    modulus: BigUint,
+    limbs: Vec<u64>,
+    // -N^-1 mod 2^64
+    inv: u64,
+    // R^2 mod N, to convert into Montgomery form
+    r2: Vec<u64>,
+}
+
+impl Montgomery {
+    pub fn new(modulus: &BigUint) -> Self {
+        let limbs = modulus.to_u64_digits();
+        debug_assert!(limbs[0] & 1 == 1, "Montgomery modulus must be odd");
+
+        // Newton's iteration doubles the number of correct low bits each round: 1 → 64
+        let mut inv = 1_u64;
+        for _ in 0..6 {
+            inv = inv.wrapping_mul(2_u64.wrapping_sub(limbs[0].wrapping_mul(inv)));
+        }
+        let r2 = (BigUint::one() << (128 * limbs.len())) % modulus;
+
+        Self {
+            modulus: modulus.clone(),
+            r2: to_limbs(&r2, limbs.len()),
+            inv: inv.wrapping_neg(),
+            limbs,
+        }
+    }
+
+    /// a mod N as fixed-width limbs
+    ///
+    /// Inputs that are already reduced, as every honest scalar and element is, skip the division.
+    pub fn limbs(&self, a: &BigUint) -> Vec<u64> {
+        if a < &self.modulus {
+            to_limbs(a, self.limbs.len())
+        } else {
+            to_limbs(&(a % &self.modulus), self.limbs.len())
+        }
+    }
+
+    /// a · R mod N
+    pub fn encode(&self, a: &BigUint) -> Vec<u64> {
+        self.mul(&Zeroizing::new(self.limbs(a)), &self.r2)
+    }
+
+    /// a · R^-1 mod N
+    pub fn decode(&self, a: &[u64]) -> BigUint {
+        let mut one = vec![0; self.limbs.len()];
+        one[0] = 1;
+        from_limbs(&Zeroizing::new(self.mul(a, &one)))
+    }
+
+    /// 1 in Montgomery form
+    pub fn one(&self) -> Vec<u64> {
+        self.encode(&BigUint::one())
+    }
+
+    /// a · b · R^-1 mod N, for a, b < N (CIOS)
+    pub fn mul(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let n = self.limbs.len();
+        let (a, b, modulus) = (&a[..n], &b[..n], &self.limbs[..]);
+        let mut t = vec![0_u64; n + 1];
+
+        // Each pass adds a · b_i plus the multiple of N that clears the low limb, then
+        // shifts that limb out, keeping t < 2N
+        for &b in b {
+            let (low, mut carry) = mac(t[0], a[0], b, 0);
+            let m = low.wrapping_mul(self.inv);
+            let (_, mut reduction_carry) = mac(low, m, modulus[0], 0);
+            for j in 1..n {
+                let (limb, c) = mac(t[j], a[j], b, carry);
+                carry = c;
+                (t[j - 1], reduction_carry) = mac(limb, m, modulus[j], reduction_carry);
+            }
+            let (limb, o1) = t[n].overflowing_add(carry);
+            let (limb, o2) = limb.overflowing_add(reduction_carry);
+            t[n - 1] = limb;
+            t[n] = o1 as u64 + o2 as u64;
+        }
+
+        let reduced = self.reduce(&t);
+        t.zeroize();
+        reduced
+    }
+
+    /// (a + b) mod N, for a, b < N
+    pub fn add(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let mut sum = vec![0; self.limbs.len() + 1];
+        let mut carry = 0;
+        for ((sum, &a), &b) in sum.iter_mut().zip(a).zip(b) {
+            (*sum, carry) = mac(a, b, 1, carry);
+        }
+        sum[self.limbs.len()] = carry;
+
+        let reduced = self.reduce(&sum);
+        sum.zeroize();
+        reduced
+    }
+
+    /// (a - b) mod N, for a, b < N
+    pub fn sub(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let mut difference = vec![0; self.limbs.len()];
+        let mut borrow = 0;
+        for ((difference, &a), &b) in difference.iter_mut().zip(a).zip(b) {
+            (*difference, borrow) = sbb(a, b, borrow);
+        }
+
+        // Add N back if that wrapped, masking N to zero if it didn't
+        let wrapped = Choice::from(borrow as u8);
+        let mut carry = 0;
+        for (difference, &modulus) in difference.iter_mut().zip(&self.limbs) {
+            let addend = u64::conditional_select(&0, &modulus, wrapped);
+            (*difference, carry) = mac(*difference, addend, 1, carry);
+        }
+        difference
+    }
+
+    /// base ^ exp for `base` in Montgomery form, with fixed windows over every limb of `exp`
+    /// and a table lookup that touches every entry, so the time depends only on `exp.len()`
+    pub fn pow(&self, base: &[u64], exp: &[u64]) -> Vec<u64> {
+        // table[d] = base ^ d
+        let mut table = vec![self.one()];
+        for d in 1..(1 << POW_WINDOW) {
+            table.push(self.mul(&table[d - 1], base));
+        }
+
+        let mut acc = self.one();
+        for index in (0..windows(exp, POW_WINDOW)).rev() {
+            for _ in 0..POW_WINDOW {
+                acc = self.mul(&acc, &acc);
+            }
+            acc = self.mul(&acc, &select(&table, window(exp, index, POW_WINDOW)));
+        }
+        acc
+    }
+
+    // t mod N for t < 2N held in one limb more than N
+    fn reduce(&self, t: &[u64]) -> Vec<u64> {
+        let n = self.limbs.len();
+
+        // Subtract N unless that borrows, selecting with a mask rather than a branch
+        let mut reduced = vec![0_u64; n];
+        let mut borrow = 0;
+        for ((reduced, &t), &modulus) in reduced.iter_mut().zip(t).zip(&self.limbs) {
+            (*reduced, borrow) = sbb(t, modulus, borrow);
+        }
+        let (_, borrow) = sbb(t[n], 0, borrow);
+        let below = Choice::from(borrow as u8);
+        for (reduced, &t) in reduced.iter_mut().zip(t) {
+            *reduced = u64::conditional_select(reduced, &t, below);
+        }
+        reduced
+    }
+}
+
+/// The number of `width`-bit windows in `exp`
+pub(crate) fn windows(exp: &[u64], width: u32) -> usize {
+    exp.len() * (u64::BITS / width) as usize
+}
+
+/// The `width` bits of `exp` starting at window `index`, for a `width` that divides 64
+pub(crate) fn window(exp: &[u64], index: usize, width: u32) -> usize {
+    let per_limb = (u64::BITS / width) as usize;
+    let shift = (index % per_limb) as u32 * width;
+    ((exp[index / per_limb] >> shift) & ((1 << width) - 1)) as usize
+}
+
+/// table[index], reading every entry so the access pattern doesn't depend on `index`
+pub(crate) fn select(table: &[Vec<u64>], index: usize) -> Vec<u64> {
+    let mut selected = vec![0; table[0].len()];
+    for (i, entry) in table.iter().enumerate() {
+        let hit = (i as u64).ct_eq(&(index as u64));
+        for (selected, limb) in selected.iter_mut().zip(entry) {
+            selected.conditional_assign(limb, hit);
+        }
+    }
+    selected
+}
+
+// a + b · c + carry → (low, high)
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let wide = a as u128 + b as u128 * c as u128 + carry as u128;
+    (wide as u64, (wide >> 64) as u64)
+}
+
+// a - b - borrow → (difference, borrow)
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let (d, o1) = a.overflowing_sub(b);
+    let (d, o2) = d.overflowing_sub(borrow);
+    (d, (o1 | o2) as u64)
+}
+
+/// The number of limbs `a` occupies, without copying them out
+pub(crate) fn limb_len(a: &BigUint) -> usize {
+    (a.bits() as usize).div_ceil(u64::BITS as usize)
+}
+
+// Allocated at full size up front, so no copy of `a` is left behind by a reallocation
+pub(crate) fn to_limbs(a: &BigUint, len: usize) -> Vec<u64> {
+    let mut limbs = Vec::with_capacity(len.max(limb_len(a)));
+    limbs.extend(a.iter_u64_digits());
+    limbs.resize(len, 0);
+    limbs
+}
+
+pub(crate) fn from_limbs(limbs: &[u64]) -> BigUint {
+    let bytes: Zeroizing<Vec<u8>> =
+        Zeroizing::new(limbs.iter().flat_map(|l| l.to_le_bytes()).collect());
+    BigUint::from_bytes_le(&bytes)
+}
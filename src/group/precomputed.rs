@@ -0,0 +1,182 @@
+use rand_core::{CryptoRng, RngCore};
+
+use super::Group;
+use crate::ParameterError;
+
+/// A group together with fixed-base tables for both of its generators
+///
+/// Building the tables costs a few dozen exponentiations and some memory up front, after
+/// which every `G ^ x` and `H ^ x` runs from the tables. Worth it for anything long-lived
+/// that exponentiates the generators over and over, like a server.
+/// The wrapper is otherwise indistinguishable from the group it wraps, down to its fingerprint.
+pub struct Precomputed<G: Group> {
+    group: G,
+    g: G::Table,
+    h: G::Table,
+}
+
+impl<G: Group> Precomputed<G> {
+    pub fn new(group: G) -> Self {
+        let (g, h) = group.generators();
+        let (g, h) = (group.precompute(g), group.precompute(h));
+
+        Self { group, g, h }
+    }
+
+    /// The underlying group
+    pub fn inner(&self) -> &G {
+        &self.group
+    }
+
+    fn table(&self, base: &G::Element) -> Option<&G::Table> {
+        let (g, h) = self.group.generators();
+        if base == g {
+            Some(&self.g)
+        } else if base == h {
+            Some(&self.h)
+        } else {
+            None
+        }
+    }
+}
+
+impl<G: Group> Group for Precomputed<G> {
+    type Element = G::Element;
+    type Scalar = G::Scalar;
+    type Table = G::Table;
+
+    fn name(&self) -> &'static str {
+        self.group.name()
+    }
+
+    fn generators(&self) -> (&G::Element, &G::Element) {
+        self.group.generators()
+    }
+
+    fn parameters(&self) -> Vec<Vec<u8>> {
+        self.group.parameters()
+    }
+
+    fn validate(&self) -> Result<(), ParameterError> {
+        self.group.validate()
+    }
+
+    fn exp(&self, base: &G::Element, exp: &G::Scalar) -> G::Element {
+        match self.table(base) {
+            Some(table) => self.group.exp_precomputed(table, exp),
+            None => self.group.exp(base, exp),
+        }
+    }
+
+    // Table lookups are constant-time too, so secrets can use them
+    fn exp_secret(&self, base: &G::Element, exp: &G::Scalar) -> G::Element {
+        match self.table(base) {
+            Some(table) => self.group.exp_precomputed(table, exp),
+            None => self.group.exp_secret(base, exp),
+        }
+    }
+
+    fn mul(&self, a: &G::Element, b: &G::Element) -> G::Element {
+        self.group.mul(a, b)
+    }
+
+    fn identity(&self) -> G::Element {
+        self.group.identity()
+    }
+
+    fn multi_exp(
+        &self,
+        (a, x): (&G::Element, &G::Scalar),
+        (b, y): (&G::Element, &G::Scalar),
+    ) -> G::Element {
+        match (self.table(a), self.table(b)) {
+            (Some(a), Some(b)) => self.mul(
+                &self.group.exp_precomputed(a, x),
+                &self.group.exp_precomputed(b, y),
+            ),
+            (Some(a), None) => self.group.multi_exp_precomputed((a, x), (b, y)),
+            (None, Some(b)) => self.group.multi_exp_precomputed((b, y), (a, x)),
+            (None, None) => self.group.multi_exp((a, x), (b, y)),
+        }
+    }
+
+    fn multi_exp_n(&self, terms: &[(&G::Element, &G::Scalar)]) -> G::Element {
+        self.group.multi_exp_n(terms)
+    }
+
+    fn precompute(&self, base: &G::Element) -> G::Table {
+        self.group.precompute(base)
+    }
+
+    fn exp_precomputed(&self, table: &G::Table, exp: &G::Scalar) -> G::Element {
+        self.group.exp_precomputed(table, exp)
+    }
+
+    fn multi_exp_precomputed(
+        &self,
+        (table, x): (&G::Table, &G::Scalar),
+        (b, y): (&G::Element, &G::Scalar),
+    ) -> G::Element {
+        self.group.multi_exp_precomputed((table, x), (b, y))
+    }
+
+    fn scalar_add(&self, a: &G::Scalar, b: &G::Scalar) -> G::Scalar {
+        self.group.scalar_add(a, b)
+    }
+
+    fn scalar_sub(&self, a: &G::Scalar, b: &G::Scalar) -> G::Scalar {
+        self.group.scalar_sub(a, b)
+    }
+
+    fn scalar_mul(&self, a: &G::Scalar, b: &G::Scalar) -> G::Scalar {
+        self.group.scalar_mul(a, b)
+    }
+
+    fn scalar_from_u128(&self, n: u128) -> G::Scalar {
+        self.group.scalar_from_u128(n)
+    }
+
+    fn scalar_from_wide(&self, bytes: &[u8]) -> G::Scalar {
+        self.group.scalar_from_wide(bytes)
+    }
+
+    fn random_scalar<R: RngCore + CryptoRng>(&self, rng: &mut R) -> G::Scalar {
+        self.group.random_scalar(rng)
+    }
+
+    fn hash_to_element(&self, data: &[u8]) -> G::Element {
+        self.group.hash_to_element(data)
+    }
+
+    fn contains_element(&self, element: &G::Element) -> bool {
+        self.group.contains_element(element)
+    }
+
+    fn contains_scalar(&self, scalar: &G::Scalar) -> bool {
+        self.group.contains_scalar(scalar)
+    }
+
+    fn element_len(&self) -> usize {
+        self.group.element_len()
+    }
+
+    fn scalar_len(&self) -> usize {
+        self.group.scalar_len()
+    }
+
+    fn encode_element(&self, element: &G::Element) -> Vec<u8> {
+        self.group.encode_element(element)
+    }
+
+    fn decode_element(&self, bytes: &[u8]) -> Option<G::Element> {
+        self.group.decode_element(bytes)
+    }
+
+    fn encode_scalar(&self, scalar: &G::Scalar) -> Vec<u8> {
+        self.group.encode_scalar(scalar)
+    }
+
+    fn decode_scalar(&self, bytes: &[u8]) -> Option<G::Scalar> {
+        self.group.decode_scalar(bytes)
+    }
+}
@@ -0,0 +1,60 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+
+use crate::group::Group;
+use crate::secret::Secret;
+
+pub use argon2::Error;
+
+/// Length of the per-user salt in bytes
+pub const SALT_LEN: usize = 16;
+
+// Wide enough that reducing the output into any of our scalar fields leaves a negligible bias
+const OUTPUT_LEN: usize = 64;
+
+/// Stretch a password with Argon2id (RFC 9106 parameters: 19 MiB, 2 passes, 1 lane)
+pub fn stretch(password: &[u8], salt: &[u8]) -> Result<Secret<[u8; OUTPUT_LEN]>, Error> {
+    let mut output = Secret::new([0; OUTPUT_LEN]);
+    stretch_into(password, salt, output.expose_mut())?;
+
+    Ok(output)
+}
+
+// Argon2id's output depends on its length, so every length stretches to unrelated bytes
+fn stretch_into(password: &[u8], salt: &[u8], output: &mut [u8]) -> Result<(), Error> {
+    let params = Params::new(
+        Params::DEFAULT_M_COST,
+        Params::DEFAULT_T_COST,
+        Params::DEFAULT_P_COST,
+        Some(output.len()),
+    )?;
+
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password, salt, output)
+}
+
+// x = Argon2id(password, salt) mod q
+pub fn derive_secret<G: Group>(
+    group: &G,
+    password: &str,
+    salt: &[u8],
+) -> Result<Secret<G::Scalar>, Error> {
+    let stretched = stretch(password.as_bytes(), salt)?;
+
+    Ok(Secret::new(group.scalar_from_wide(stretched.expose())))
+}
+
+// (x1, .., xn) = Argon2id(password, salt), cut into n pieces of 64 bytes, each mod q
+// For credentials made of several secrets, which a single password then stands in for
+pub fn derive_secrets<G: Group, const N: usize>(
+    group: &G,
+    password: &str,
+    salt: &[u8],
+) -> Result<Secret<[G::Scalar; N]>, Error> {
+    let mut stretched = Secret::new(vec![0; N * OUTPUT_LEN]);
+    stretch_into(password.as_bytes(), salt, stretched.expose_mut())?;
+
+    let pieces = stretched.expose();
+    Ok(Secret::new(std::array::from_fn(|i| {
+        group.scalar_from_wide(&pieces[i * OUTPUT_LEN..(i + 1) * OUTPUT_LEN])
+    })))
+}
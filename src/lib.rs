@@ -1,32 +1,70 @@
 use num_bigint::BigUint;
-use num_traits::One;
+
+pub mod chaum_pedersen;
+pub mod consts;
+pub mod context;
+pub mod group;
+pub mod kdf;
+pub mod nonce;
+pub mod proof;
+pub mod protocol;
+pub mod secret;
+pub mod sigma;
+mod validation;
+
+pub use context::{Context, PROTOCOL_VERSION};
+pub use validation::ParameterError;
+
+/// Domain separation tag for non-interactive login proofs
+pub const LOGIN_CONTEXT: &[u8] = b"zkp-auth/login/v1";
+
+/// Context bound into a non-interactive login proof made at `timestamp` (seconds since the UNIX epoch)
+pub fn login_context(timestamp: u64) -> Vec<u8> {
+    [LOGIN_CONTEXT, &timestamp.to_be_bytes()].concat()
+}
+
+/// Domain separation tag for the proof that comes with every registered statement
+pub const REGISTRATION_CONTEXT: &[u8] = b"zkp-auth/register/v1";
+
+/// Domain separation tag for anonymous membership proofs
+pub const MEMBERSHIP_CONTEXT: &[u8] = b"zkp-auth/membership/v1";
+
+/// Context bound into an anonymous membership proof made at `timestamp` (seconds since the UNIX epoch)
+pub fn membership_context(timestamp: u64) -> Vec<u8> {
+    [MEMBERSHIP_CONTEXT, &timestamp.to_be_bytes()].concat()
+}
 
 #[allow(non_snake_case)]
+#[derive(Debug, Clone)]
 pub struct Parameters {
     pub G: BigUint,
     pub P: BigUint,
     pub Q: BigUint,
     pub H: BigUint,
+    /// What every challenge and fingerprint calls the group: its registry name, or `modp`
+    pub name: &'static str,
 }
 
+// The MODP-specific API predates the `Group` abstraction and is kept as a thin wrapper over it
 impl Parameters {
     // y1 = (G ^ x) mod P
     // y2 = (H ^ x) mod P
     pub fn obfuscate(&self, x: &BigUint) -> (BigUint, BigUint) {
-        let y1 = self.G.modpow(&x, &self.P);
-        let y2 = self.H.modpow(&x, &self.P);
+        chaum_pedersen::obfuscate(self, x)
+    }
 
-        (y1, y2)
+    // x = Argon2id(password, salt) mod Q
+    pub fn derive_secret(
+        &self,
+        password: &str,
+        salt: &[u8],
+    ) -> Result<secret::Secret<BigUint>, kdf::Error> {
+        kdf::derive_secret(self, password, salt)
     }
 
-    // s = (k - (c * x)) mod Q (if k >= c * x) else Q - ((c * x) - k mod Q)
+    // s = (k - (c * x)) mod Q
     pub fn solve_challenge(&self, k: &BigUint, c: &BigUint, x: &BigUint) -> BigUint {
-        let cx = c * x;
-        if k >= &cx {
-            (k - cx).modpow(&BigUint::one(), &self.Q)
-        } else {
-            &self.Q - (cx - k).modpow(&BigUint::one(), &self.Q)
-        }
+        chaum_pedersen::solve_challenge(self, k, c, x)
     }
 
     // r1 ⇔ v1 = (((G ^ s) mod P) * ((y1 ^ c) mod P)) mod P
@@ -38,66 +76,77 @@ impl Parameters {
         c: &BigUint,
         s: &BigUint,
     ) -> bool {
-        let gsp = self.G.modpow(s, &self.P);
-        let y1cp = y1.modpow(c, &self.P);
-        let v1 = (gsp * y1cp).modpow(&BigUint::one(), &self.P);
+        chaum_pedersen::verify(self, (y1, y2), (r1, r2), c, s)
+    }
+
+    // ((r1, r2), s) such that verify((y1, y2), (r1, r2), c, s) holds, for a random s and no x
+    pub fn simulate<R: rand_core::RngCore + rand_core::CryptoRng>(
+        &self,
+        (y1, y2): (&BigUint, &BigUint),
+        c: &BigUint,
+        rng: &mut R,
+    ) -> ((BigUint, BigUint), BigUint) {
+        chaum_pedersen::simulate(self, (y1, y2), c, rng)
+    }
 
-        let hsp = self.H.modpow(s, &self.P);
-        let y2cp = y2.modpow(c, &self.P);
-        let v2 = (hsp * y2cp).modpow(&BigUint::one(), &self.P);
+    // verify(...) for a transcript written out with `proof::Transcript::encode`
+    pub fn verify_transcript(&self, bytes: &[u8]) -> Result<bool, proof::DecodeError> {
+        let proof::Transcript {
+            statement,
+            commitment,
+            challenge,
+            response,
+        } = proof::Transcript::decode(self, bytes)?;
 
-        r1 == &v1 && r2 == &v2
+        Ok(self.verify(
+            (&statement.y1, &statement.y2),
+            (&commitment.r1, &commitment.r2),
+            &challenge.c,
+            &response.s,
+        ))
     }
-}
 
-pub mod consts {
-    use super::*;
+    // verify(...) for every ((y1, y2), (r1, r2), c, s) at once, returning the indices that fail
+    pub fn verify_batch<R: rand_core::RngCore + rand_core::CryptoRng>(
+        &self,
+        entries: &[chaum_pedersen::BatchEntry<'_, Parameters>],
+        rng: &mut R,
+    ) -> Result<(), Vec<usize>> {
+        chaum_pedersen::verify_batch(self, entries, rng)
+    }
 
-    use lazy_static::lazy_static;
-
-    lazy_static! {
-        pub static ref PARAMS: Parameters = Parameters {
-            // Chosen from Internet Engineering Task Force RFC 3526: https://datatracker.ietf.org/doc/rfc3526
-            // === 2048-bit MODP group ===
-            G: BigUint::from(2_u8),
-            // Flipped to be compatible with BigUint::new()'s endianness
-            P: BigUint::new(vec![
-                0xFFFFFFFF, 0xFFFFFFFF, 0x8AACAA68, 0x15728E5A, 0x98FA0510, 0x15D22618, 0xEA956AE5, 0x3995497C,
-                0x95581718, 0xDE2BCBF6, 0x6F4C52C9, 0xB5C55DF0, 0xEC07A28F, 0x9B2783A2, 0x180E8603, 0xE39E772C,
-                0x2E36CE3B, 0x32905E46, 0xCA18217C, 0xF1746C08, 0x4ABC9804, 0x670C354E, 0x7096966D, 0x9ED52907,
-                0x208552BB, 0x1C62F356, 0xDCA3AD96, 0x83655D23, 0xFD24CF5F, 0x69163FA8, 0x1C55D39A, 0x98DA4836,
-                0xA163BF05, 0xC2007CB8, 0xECE45B3D, 0x49286651, 0x7C4B1FE6, 0xAE9F2411, 0x5A899FA5, 0xEE386BFB,
-                0xF406B7ED, 0x0BFF5CB6, 0xA637ED6B, 0xF44C42E9, 0x625E7EC6, 0xE485B576, 0x6D51C245, 0x4FE1356D,
-                0xF25F1437, 0x302B0A6D, 0xCD3A431B, 0xEF9519B3, 0x8E3404DD, 0x514A0879, 0x3B139B22, 0x020BBEA6,
-                0x8A67CC74, 0x29024E08, 0x80DC1CD1, 0xC4C6628B, 0x2168C234, 0xC90FDAA2, 0xFFFFFFFF, 0xFFFFFFFF,
-            ]),
-            // === 2048-bit MODP group ===
-
-            // q where q = (p - 1) / 2 and q is a prime
-            Q: BigUint::new(vec![
-                0xFFFFFFFF, 0x7FFFFFFF, 0x45565534, 0x0AB9472D, 0x4C7D0288, 0x8AE9130C, 0x754AB572, 0x1CCAA4BE,
-                0x4AAC0B8C, 0xEF15E5FB, 0x37A62964, 0xDAE2AEF8, 0x7603D147, 0xCD93C1D1, 0x0C074301, 0xF1CF3B96,
-                0x171B671D, 0x19482F23, 0x650C10BE, 0x78BA3604, 0x255E4C02, 0xB3861AA7, 0xB84B4B36, 0xCF6A9483,
-                0x1042A95D, 0x0E3179AB, 0xEE51D6CB, 0xC1B2AE91, 0x7E9267AF, 0x348B1FD4, 0x0E2AE9CD, 0xCC6D241B,
-                0x50B1DF82, 0xE1003E5C, 0xF6722D9E, 0x24943328, 0xBE258FF3, 0xD74F9208, 0xAD44CFD2, 0xF71C35FD,
-                0x7A035BF6, 0x85FFAE5B, 0xD31BF6B5, 0x7A262174, 0x312F3F63, 0xF242DABB, 0xB6A8E122, 0xA7F09AB6,
-                0xF92F8A1B, 0x98158536, 0xE69D218D, 0xF7CA8CD9, 0xC71A026E, 0x28A5043C, 0x1D89CD91, 0x0105DF53,
-                0x4533E63A, 0x94812704, 0xC06E0E68, 0x62633145, 0x10B4611A, 0xE487ED51, 0xFFFFFFFF, 0x7FFFFFFF,
-            ]),
-
-            // ƒ = (2 ^ 127) - 1 = 170141183460469231731687303715884105727 (prime)
-            // h = (G ^ ƒ) mod P
-            H: BigUint::new(vec![
-                0x385370ED, 0xC1B26557, 0x5F1A04B2, 0x54D6CCDA, 0x0B9E9E0A, 0xFB6A1CAB, 0x0FD31F78, 0x74E8FF05,
-                0x091D0DE4, 0x36C78E3B, 0x1F4BD125, 0x02D27994, 0x8FE002EA, 0x3B3976AF, 0x6D62BB0D, 0xE6753CA0,
-                0x62026788, 0x4416C825, 0x30055036, 0x897A1FAD, 0x88C1CC09, 0x626E15D8, 0x0A31A4FE, 0xD56CE35E,
-                0x444AA956, 0x288D266E, 0x8973E0C7, 0x846AFF94, 0x5F817F1F, 0xDA255E98, 0xB1F36B26, 0x2999B5DD,
-                0xB3D5C81F, 0x2FECB08E, 0x307BA327, 0xB1B58613, 0x24CD4AEC, 0x1A5BD0B2, 0x2D039C2C, 0x99BF0335,
-                0x8B7FD66A, 0xA3CE7D7B, 0xA2119B56, 0xDCF89677, 0x928B2EC0, 0x0597717A, 0x707FC0E7, 0x53015271,
-                0xA0F485D2, 0x2D9B492B, 0x8CCDA987, 0xEEA3F80D, 0x3B3D8C75, 0x66F489DD, 0x0EA7DA9D, 0xE46AC006,
-                0x5C4917F4, 0xC188973E, 0xD127BDC1, 0x51049549, 0x3BFDB0B6, 0x0DB833C1, 0xB537228E, 0x0B4634B1,
-            ]),
-        };
+    // c = SHA-512("modp", G, H, P, Q, y1, y2, r1, r2, user, context) mod Q
+    pub fn challenge(
+        &self,
+        (y1, y2): (&BigUint, &BigUint),
+        (r1, r2): (&BigUint, &BigUint),
+        user: &str,
+        context: &[u8],
+    ) -> BigUint {
+        chaum_pedersen::challenge(self, (y1, y2), (r1, r2), user, context)
+    }
+
+    // (r1, r2) = obfuscate(k), c = challenge(...), s = solve_challenge(k, c, x)
+    pub fn prove(
+        &self,
+        x: &BigUint,
+        k: &BigUint,
+        user: &str,
+        context: &[u8],
+    ) -> ((BigUint, BigUint), BigUint) {
+        chaum_pedersen::prove(self, x, k, user, context)
+    }
+
+    // verify(...) with c = challenge(...)
+    pub fn verify_non_interactive(
+        &self,
+        (y1, y2): (&BigUint, &BigUint),
+        (r1, r2): (&BigUint, &BigUint),
+        s: &BigUint,
+        user: &str,
+        context: &[u8],
+    ) -> bool {
+        chaum_pedersen::verify_non_interactive(self, (y1, y2), (r1, r2), s, user, context)
     }
 }
 
@@ -109,8 +158,13 @@ pub mod proto {
     pub use super::zkp_auth::{
         auth_client::AuthClient,
         auth_server::{Auth, AuthServer},
-        AuthenticationAnswerRequest, AuthenticationAnswerResponse, AuthenticationChallengeRequest,
-        AuthenticationChallengeResponse, RegisterRequest, RegisterResponse,
+        composed_authentication_request::Proof as ComposedProof,
+        AndProof, AuthenticationAnswerRequest, AuthenticationAnswerResponse,
+        AuthenticationChallengeRequest, AuthenticationChallengeResponse, Challenge, Commitment,
+        ComposedAuthenticationRequest, Credential, MembersRequest, MembersResponse,
+        MembershipRequest, MembershipResponse, NonInteractiveAuthenticationRequest, OrProof,
+        ParametersRequest, ParametersResponse, Proof, RegisterRequest, RegisterResponse, Response,
+        SaltRequest, SaltResponse, Statement,
     };
 }
 
@@ -119,7 +173,9 @@ mod tests {
     #![allow(non_snake_case)]
 
     use super::*;
+    use group::Group;
     use num_bigint::BigUint;
+    use num_traits::One;
     use zkp_utils::{random, string};
 
     #[test]
@@ -129,7 +185,13 @@ mod tests {
         let G = BigUint::from(4_u8);
         let H = BigUint::from(9_u8);
 
-        let params = Parameters { G, P, Q, H };
+        let params = Parameters {
+            G,
+            P,
+            Q,
+            H,
+            name: "modp",
+        };
 
         let x = BigUint::from(6_u8);
 
@@ -146,7 +208,13 @@ mod tests {
         let G = BigUint::from(4_u8);
         let H = BigUint::from(9_u8);
 
-        let params = Parameters { G, P, Q, H };
+        let params = Parameters {
+            G,
+            P,
+            Q,
+            H,
+            name: "modp",
+        };
 
         let k = BigUint::from(7_u8);
         let c = BigUint::from(4_u8);
@@ -162,7 +230,13 @@ mod tests {
         let G = BigUint::from(4_u8);
         let H = BigUint::from(9_u8);
 
-        let params = Parameters { G, P, Q, H };
+        let params = Parameters {
+            G,
+            P,
+            Q,
+            H,
+            name: "modp",
+        };
 
         let y1 = BigUint::from(2_u8);
         let y2 = BigUint::from(3_u8);
@@ -183,7 +257,13 @@ mod tests {
         let G = BigUint::from(4_u8);
         let H = BigUint::from(9_u8);
 
-        let params = Parameters { G, P, Q, H };
+        let params = Parameters {
+            G,
+            P,
+            Q,
+            H,
+            name: "modp",
+        };
 
         let x = BigUint::from(6_u8);
 
@@ -198,6 +278,22 @@ mod tests {
         assert!(params.verify((&y1, &y2), (&r1, &r2), &c, &s));
     }
 
+    #[test]
+    fn secret_derivation() {
+        let salt_a = [0xA5; kdf::SALT_LEN];
+        let salt_b = [0x5A; kdf::SALT_LEN];
+
+        let derive = |password, salt| consts::PARAMS.derive_secret(password, salt).unwrap();
+        let x = derive("oppenheimer", &salt_a);
+        let x = x.expose();
+
+        assert!(x < &consts::PARAMS.Q);
+        assert_eq!(x, derive("oppenheimer", &salt_a).expose());
+        assert_ne!(x, derive("oppenheimer", &salt_b).expose());
+        assert_ne!(x, derive("barbie", &salt_a).expose());
+        assert_ne!(x, &string::as_biguint("oppenheimer"));
+    }
+
     #[test]
     fn authentication() {
         // Registration
@@ -224,4 +320,904 @@ mod tests {
 
         assert!(!consts::PARAMS.verify((&y1, &y2), (&r1, &r2), &c, &s));
     }
+
+    #[test]
+    fn non_interactive_authentication() {
+        let context = login_context(1_690_000_000);
+
+        // Registration
+        let x = string::as_biguint("oppenheimer");
+        let (y1, y2) = consts::PARAMS.obfuscate(&x);
+
+        // Authentication attempt 1
+        let k = random::biguint(&consts::PARAMS.Q);
+        let ((r1, r2), s) = consts::PARAMS.prove(&x, &k, "peggy", &context);
+
+        assert!(consts::PARAMS.verify_non_interactive(
+            (&y1, &y2),
+            (&r1, &r2),
+            &s,
+            "peggy",
+            &context
+        ));
+
+        // The same proof must not carry over to another user or context
+        assert!(!consts::PARAMS.verify_non_interactive(
+            (&y1, &y2),
+            (&r1, &r2),
+            &s,
+            "victor",
+            &context
+        ));
+        assert!(!consts::PARAMS.verify_non_interactive(
+            (&y1, &y2),
+            (&r1, &r2),
+            &s,
+            "peggy",
+            &login_context(1_690_000_001)
+        ));
+
+        // Authentication attempt 2: Should fail (Incorrect password)
+        let x = string::as_biguint("barbie");
+
+        let k = random::biguint(&consts::PARAMS.Q);
+        let ((r1, r2), s) = consts::PARAMS.prove(&x, &k, "peggy", &context);
+
+        assert!(!consts::PARAMS.verify_non_interactive(
+            (&y1, &y2),
+            (&r1, &r2),
+            &s,
+            "peggy",
+            &context
+        ));
+    }
+
+    #[test]
+    fn hash_to_subgroup() {
+        let h = consts::PARAMS.hash_to_element(b"zkp-auth/test");
+
+        assert!(h > BigUint::one() && h < consts::PARAMS.P);
+        assert_eq!(
+            h.modpow(&consts::PARAMS.Q, &consts::PARAMS.P),
+            BigUint::one()
+        );
+        assert_ne!(h, consts::PARAMS.hash_to_element(b"zkp-auth/test2"));
+    }
+
+    #[test]
+    fn parameter_validation() {
+        consts::PARAMS.validate().unwrap();
+        Parameters::new(4_u8.into(), 23_u8.into(), 11_u8.into(), 9_u8.into()).unwrap();
+
+        #[cfg(feature = "ristretto")]
+        consts::RISTRETTO255.validate().unwrap();
+
+        let check = |G: u8, P: u8, Q: u8, H: u8| {
+            Parameters::new(G.into(), P.into(), Q.into(), H.into()).err()
+        };
+
+        // 21 = 3 * 7
+        assert_eq!(check(4, 21, 10, 9), Some(ParameterError::CompositeOrder));
+        assert_eq!(check(4, 25, 11, 9), Some(ParameterError::NotSafePrime));
+        // 5 is a quadratic non-residue mod 23, so it generates the whole group of order 22
+        assert_eq!(
+            check(5, 23, 11, 9),
+            Some(ParameterError::InvalidGenerator("G"))
+        );
+        assert_eq!(
+            check(4, 23, 11, 1),
+            Some(ParameterError::InvalidGenerator("H"))
+        );
+        assert_eq!(
+            check(4, 23, 11, 4),
+            Some(ParameterError::DependentGenerators)
+        );
+
+        // 3215031751 = 151 * 751 * 28351 is a strong pseudoprime to bases 2, 3, 5 and 7
+        let (Q, P) = (BigUint::from(3215031751_u64), BigUint::from(6430063503_u64));
+        assert_eq!(
+            Parameters::new(4_u8.into(), P, Q, 9_u8.into()).err(),
+            Some(ParameterError::CompositeOrder)
+        );
+    }
+
+    #[test]
+    fn checked_decoding() {
+        let params = Parameters::new(4_u8.into(), 23_u8.into(), 11_u8.into(), 9_u8.into()).unwrap();
+
+        assert_eq!(params.decode_element(&[2]), Some(BigUint::from(2_u8)));
+        // Identity, order 2, order 22 (non-residue) and out of range
+        for bytes in [&[][..], &[1], &[22], &[5], &[23], &[0, 25]] {
+            assert_eq!(params.decode_element(bytes), None, "{:?}", bytes);
+        }
+
+        assert_eq!(params.decode_scalar(&[10]), Some(BigUint::from(10_u8)));
+        assert_eq!(params.decode_scalar(&[11]), None);
+
+        // s + Q is an equivalent exponent, but not a canonical response
+        let (y1, y2) = (BigUint::from(2_u8), BigUint::from(3_u8));
+        let (r1, r2) = (BigUint::from(8_u8), BigUint::from(4_u8));
+        let c = BigUint::from(4_u8);
+        let s = BigUint::from(5_u8 + 11);
+
+        assert!(!params.verify((&y1, &y2), (&r1, &r2), &c, &s));
+
+        #[cfg(feature = "ristretto")]
+        {
+            let group = &*consts::RISTRETTO255;
+            assert_eq!(group.decode_element(&[0; 32]), None);
+            assert_eq!(group.decode_element(&[0xFF; 32]), None);
+            assert_eq!(group.decode_scalar(&[0xFF; 32]), None);
+        }
+    }
+
+    #[test]
+    fn typed_proofs() {
+        use proof::{Challenge, Commitment, DecodeError, Proof, Response, Statement};
+
+        let group = &*consts::PARAMS;
+        let context = login_context(1_690_000_000);
+        let x = string::as_biguint("oppenheimer");
+        let k = random::biguint(&group.Q);
+        let statement = Statement::new(group, &x);
+
+        // Interactive
+        let commitment = Commitment::new(group, &k);
+        let challenge = Challenge::random(group, &mut random::rng());
+        let response = Response::new(group, &k, &challenge, &x);
+        assert!(statement.verify(group, &commitment, &challenge, &response));
+
+        // Non-interactive, round-tripped through both encodings
+        let proof = Proof::new(group, &x, &k, "peggy", &context);
+        assert_eq!(proof.commitment, commitment);
+        assert!(statement.verify_proof(group, &proof, "peggy", &context));
+
+        let encoded = proof.encode(group);
+        assert_eq!(encoded.len(), 2 * group.element_len() + group.scalar_len());
+        assert_eq!(Proof::decode(group, &encoded), Ok(proof.clone()));
+        assert_eq!(
+            Proof::decode(group, &encoded[1..]),
+            Err(DecodeError::InvalidLength {
+                expected: encoded.len(),
+                actual: encoded.len() - 1
+            })
+        );
+
+        let message = proto::Proof::from((group, &proof));
+        assert_eq!(Proof::try_from((group, message.clone())), Ok(proof));
+
+        // Fixed width: small values are left-padded, and unpadded encodings are rejected
+        let one = group.encode_scalar(&BigUint::one());
+        assert_eq!(one.len(), group.scalar_len());
+        assert_eq!(group.decode_scalar(&one), Some(BigUint::one()));
+        assert_eq!(group.decode_scalar(&[1]), None);
+
+        // Fields are named in errors
+        let mut swapped = message.clone();
+        swapped.commitment.as_mut().unwrap().r1 = vec![0; group.element_len()];
+        assert_eq!(
+            Proof::<_>::try_from((group, swapped)),
+            Err(DecodeError::InvalidElement("r1"))
+        );
+        let truncated = proto::Proof {
+            response: None,
+            ..message
+        };
+        assert_eq!(
+            Proof::<_>::try_from((group, truncated)),
+            Err(DecodeError::Missing("response"))
+        );
+    }
+
+    #[test]
+    fn protocol_state_machines() {
+        use proof::Statement;
+        use protocol::{ChallengedVerifier, Prover, Verifier, VerifyError};
+
+        let group = &*consts::PARAMS;
+        let mut rng = random::rng();
+        let x = string::as_biguint("oppenheimer");
+        let statement = Statement::new(group, &x);
+
+        // Interactive: commit, challenge, respond, verify
+        let (prover, commitment) = Prover::commit_random(group, &mut rng);
+        let (verifier, challenge) =
+            Verifier::new(statement.clone()).challenge(group, commitment, "peggy", b"", &mut rng);
+        assert_eq!(verifier.challenge(), &challenge);
+        let response = prover.respond(group, &challenge, &x);
+        assert_eq!(verifier.verify(group, &response), Ok(()));
+
+        // Wrong secret
+        let (prover, commitment) = Prover::commit_random(group, &mut rng);
+        let (verifier, challenge) =
+            Verifier::new(statement.clone()).challenge(group, commitment, "peggy", b"", &mut rng);
+        let response = prover.respond(group, &challenge, &string::as_biguint("barbie"));
+        assert_eq!(
+            verifier.verify(group, &response),
+            Err(VerifyError::Rejected)
+        );
+
+        // Picked back up from its parts, as a server keeping it in storage does
+        let (prover, commitment) = Prover::commit_random(group, &mut rng);
+        let (verifier, challenge) =
+            Verifier::new(statement.clone()).challenge(group, commitment, "peggy", b"", &mut rng);
+        let resumed = ChallengedVerifier::resume(
+            statement.clone(),
+            verifier.commitment().clone(),
+            verifier.challenge().clone(),
+        );
+        let response = prover.respond(group, &challenge, &x);
+        assert_eq!(resumed.verify(group, &response), Ok(()));
+
+        // Non-interactive
+        let context = login_context(1_690_000_000);
+        let (prover, _) = Prover::commit_random(group, &mut rng);
+        let proof = prover.prove(group, &x, "peggy", &context);
+        let verifier = Verifier::new(statement);
+        assert_eq!(
+            verifier.verify_proof(group, &proof, "peggy", &context),
+            Ok(())
+        );
+        assert_eq!(
+            verifier.verify_proof(group, &proof, "victor", &context),
+            Err(VerifyError::Rejected)
+        );
+    }
+
+    #[test]
+    fn sigma_protocols() {
+        use proof::{DecodeError, Message, Proof};
+        use protocol::VerifyError;
+        use sigma::{ChaumPedersen, Okamoto, Prover, Schnorr, SigmaProtocol, Verifier};
+
+        fn check<G: Group, P: SigmaProtocol<G>>(group: &G, x: &P::Witness, wrong: &P::Witness) {
+            let mut rng = random::rng();
+            let context = login_context(1_690_000_000);
+            let statement = P::statement(group, x);
+
+            // Interactive, and with the wrong witness
+            let (prover, commitment) = Prover::<G, P>::commit_random(group, &mut rng);
+            let (verifier, challenge) = Verifier::<G, P>::new(statement.clone())
+                .challenge(group, commitment, "peggy", b"", &mut rng);
+            let response = prover.respond(group, &challenge, x);
+            assert_eq!(verifier.verify(group, &response), Ok(()), "{}", P::ID);
+
+            let (prover, commitment) = Prover::<G, P>::commit_random(group, &mut rng);
+            let (verifier, challenge) = Verifier::<G, P>::new(statement.clone())
+                .challenge(group, commitment, "peggy", b"", &mut rng);
+            let response = prover.respond(group, &challenge, wrong);
+            assert_eq!(
+                verifier.verify(group, &response),
+                Err(VerifyError::Rejected)
+            );
+
+            // Non-interactive, bound to the user
+            let (prover, _) = Prover::<G, P>::commit_random(group, &mut rng);
+            let proof = prover.prove(group, x, "peggy", &context);
+            let verifier = Verifier::<G, P>::new(statement.clone());
+            assert_eq!(
+                verifier.verify_proof(group, &proof, "peggy", &context),
+                Ok(())
+            );
+            assert_eq!(
+                verifier.verify_proof(group, &proof, "victor", &context),
+                Err(VerifyError::Rejected)
+            );
+
+            // Through the wire format
+            let message = proto::Proof::from((group, &proof));
+            assert_eq!(Proof::<G, P>::try_from((group, message)), Ok(proof));
+            let message = statement.to_proto(group);
+            assert_eq!(P::Statement::try_from((group, message)), Ok(statement));
+        }
+
+        fn check_group<G: Group>(group: &G) {
+            let mut rng = random::rng();
+            let [x, y, z] = [(); 3].map(|_| group.random_scalar(&mut rng));
+
+            check::<G, ChaumPedersen>(group, &x, &y);
+            check::<G, Schnorr>(group, &x, &y);
+            check::<G, Okamoto>(group, &[x.clone(), y.clone()], &[x, z]);
+        }
+
+        check_group(&*consts::PARAMS);
+        #[cfg(feature = "ristretto")]
+        check_group(&*consts::RISTRETTO255);
+
+        // A Chaum-Pedersen statement isn't a Schnorr one, nor a Schnorr response an Okamoto one
+        let group = &*consts::PARAMS;
+        let x = string::as_biguint("oppenheimer");
+        let message = ChaumPedersen::statement(group, &x).to_proto(group);
+        assert_eq!(
+            sigma::schnorr::Statement::try_from((group, message)),
+            Err(DecodeError::Unexpected("y2"))
+        );
+        let message = proto::Response {
+            s: group.encode_scalar(&x),
+            s2: Vec::new(),
+        };
+        assert_eq!(
+            sigma::okamoto::Response::try_from((group, message.clone())),
+            Err(DecodeError::InvalidScalar("s2"))
+        );
+        assert_eq!(
+            proof::Response::try_from((
+                group,
+                proto::Response {
+                    s2: message.s.clone(),
+                    ..message
+                }
+            )),
+            Err(DecodeError::Unexpected("s2"))
+        );
+
+        assert_eq!(
+            "okamoto".parse::<sigma::ProtocolId>().ok(),
+            Some(sigma::ProtocolId::Okamoto)
+        );
+        assert!("schnor".parse::<sigma::ProtocolId>().is_err());
+    }
+
+    #[test]
+    fn registration_proofs() {
+        use proof::Statement;
+        use protocol::{Prover, Verifier, VerifyError};
+
+        let group = &*consts::PARAMS;
+        let mut rng = random::rng();
+        let x = string::as_biguint("oppenheimer");
+
+        let (prover, _) = Prover::commit_random(group, &mut rng);
+        let proof = prover.prove(group, &x, "peggy", REGISTRATION_CONTEXT);
+        let verifier = Verifier::new(Statement::new(group, &x));
+        assert_eq!(
+            verifier.verify_proof(group, &proof, "peggy", REGISTRATION_CONTEXT),
+            Ok(())
+        );
+        // Not for another user, nor as a login
+        assert_eq!(
+            verifier.verify_proof(group, &proof, "victor", REGISTRATION_CONTEXT),
+            Err(VerifyError::Rejected)
+        );
+        assert_eq!(
+            verifier.verify_proof(group, &proof, "peggy", &login_context(1_690_000_000)),
+            Err(VerifyError::Rejected)
+        );
+
+        // Halves with different exponents can't be proven with either of them
+        let (y1, _) = group.obfuscate(&x);
+        let (_, y2) = group.obfuscate(&string::as_biguint("barbie"));
+        let mismatched = Verifier::new(Statement { y1, y2 });
+        for x in [x, string::as_biguint("barbie")] {
+            let (prover, _) = Prover::commit_random(group, &mut rng);
+            let proof = prover.prove(group, &x, "peggy", REGISTRATION_CONTEXT);
+            assert_eq!(
+                mismatched.verify_proof(group, &proof, "peggy", REGISTRATION_CONTEXT),
+                Err(VerifyError::Rejected)
+            );
+        }
+    }
+
+    #[test]
+    fn context_binding() {
+        use proof::Statement;
+        use protocol::{Prover, Verifier, VerifyError};
+        use sigma::compose::OrProof;
+
+        let group = &*consts::PARAMS;
+        let mut rng = random::rng();
+        let x = string::as_biguint("oppenheimer");
+        let purpose = login_context(1_690_000_000);
+
+        let context = Context::new("zkp-auth", "default");
+        let others = [
+            Context::new("elsewhere", "default"),
+            Context::new("zkp-auth", "staging"),
+            Context {
+                version: PROTOCOL_VERSION + 1,
+                ..context.clone()
+            },
+        ];
+        // No two contexts share their bytes, even when their fields run together
+        assert_ne!(
+            Context::new("zkp-auth", "default").bind("peggy", &purpose),
+            Context::new("zkp-authdefault", "").bind("peggy", &purpose)
+        );
+
+        let (prover, _) = Prover::commit_random(group, &mut rng);
+        let proof = prover.prove(group, &x, "peggy", &context.bind("peggy", &purpose));
+        let verifier = Verifier::new(Statement::new(group, &x));
+        assert_eq!(
+            verifier.verify_proof(group, &proof, "peggy", &context.bind("peggy", &purpose)),
+            Ok(())
+        );
+        for other in &others {
+            assert_eq!(
+                verifier.verify_proof(group, &proof, "peggy", &other.bind("peggy", &purpose)),
+                Err(VerifyError::Rejected)
+            );
+        }
+        // Nor can the user be swapped in the context alone
+        assert_eq!(
+            verifier.verify_proof(group, &proof, "peggy", &context.bind("victor", &purpose)),
+            Err(VerifyError::Rejected)
+        );
+
+        // Membership proofs are bound to the deployment too
+        let ring: Vec<_> = ["oppenheimer", "barbie"]
+            .into_iter()
+            .map(|x| Statement::new(group, &string::as_biguint(x)))
+            .collect();
+        let proof = OrProof::membership(group, &ring, 0, &x, &context.bind("", &purpose), &mut rng);
+        assert_eq!(
+            proof.verify_membership(group, &ring, &context.bind("", &purpose)),
+            Ok(())
+        );
+        for other in &others {
+            assert_eq!(
+                proof.verify_membership(group, &ring, &other.bind("", &purpose)),
+                Err(VerifyError::Rejected)
+            );
+        }
+    }
+
+    #[test]
+    fn composed_proofs() {
+        use proof::Statement;
+        use protocol::VerifyError;
+        use sigma::compose::{AndProof, OrProof};
+
+        let group = &*consts::PARAMS;
+        let mut rng = random::rng();
+        let context = login_context(1_690_000_000);
+        let xs: Vec<_> = ["oppenheimer", "barbie", "recovery"]
+            .into_iter()
+            .map(string::as_biguint)
+            .collect();
+        let statements: Vec<_> = xs.iter().map(|x| Statement::new(group, x)).collect();
+
+        // AND: every secret, bound to the user and to the order of the statements
+        let proof = AndProof::new(group, &xs, "peggy", &context, &mut rng);
+        assert_eq!(proof.verify(group, &statements, "peggy", &context), Ok(()));
+        assert_eq!(
+            proof.verify(group, &statements, "victor", &context),
+            Err(VerifyError::Rejected)
+        );
+        let reversed: Vec<_> = statements.iter().rev().cloned().collect();
+        assert_eq!(
+            proof.verify(group, &reversed, "peggy", &context),
+            Err(VerifyError::Rejected)
+        );
+        assert_eq!(
+            proof.verify(group, &statements[..2], "peggy", &context),
+            Err(VerifyError::Rejected)
+        );
+
+        let message = proto::AndProof::from((group, &proof));
+        assert_eq!(AndProof::try_from((group, message)), Ok(proof));
+
+        // OR: any one secret, and the branches look alike whichever it is
+        for (index, x) in xs.iter().enumerate() {
+            let proof = OrProof::new(group, &statements, index, x, "peggy", &context, &mut rng);
+            assert_eq!(proof.verify(group, &statements, "peggy", &context), Ok(()));
+            assert_eq!(
+                proof.verify(group, &statements, "victor", &context),
+                Err(VerifyError::Rejected)
+            );
+
+            let message = proto::OrProof::from((group, &proof));
+            assert_eq!(OrProof::try_from((group, message)), Ok(proof.clone()));
+
+            // Shifting the challenge between branches breaks both of them
+            let mut shifted = proof;
+            let one = group.scalar_from_u128(1);
+            shifted.challenges[0].c = group.scalar_add(&shifted.challenges[0].c, &one);
+            shifted.challenges[1].c = group.scalar_sub(&shifted.challenges[1].c, &one);
+            assert_eq!(
+                shifted.verify(group, &statements, "peggy", &context),
+                Err(VerifyError::Rejected)
+            );
+        }
+
+        let wrong = string::as_biguint("ken");
+        let proof = OrProof::new(group, &statements, 1, &wrong, "peggy", &context, &mut rng);
+        assert_eq!(
+            proof.verify(group, &statements, "peggy", &context),
+            Err(VerifyError::Rejected)
+        );
+
+        // Membership: an OR proof naming nobody, which doesn't pass for a login
+        let context = membership_context(1_690_000_000);
+        let proof = OrProof::membership(group, &statements, 2, &xs[2], &context, &mut rng);
+        assert_eq!(
+            proof.verify_membership(group, &statements, &context),
+            Ok(())
+        );
+        assert_eq!(
+            proof.verify_membership(group, &statements, &login_context(1_690_000_000)),
+            Err(VerifyError::Rejected)
+        );
+        assert_eq!(
+            proof.verify(group, &statements, "peggy", &context),
+            Err(VerifyError::Rejected)
+        );
+    }
+
+    #[test]
+    fn simulated_transcripts() {
+        use proof::{Challenge, DecodeError, Statement, Transcript};
+
+        fn check<G: Group>(group: &G) {
+            let mut rng = random::rng();
+            let statement = Statement::new(group, &group.random_scalar(&mut rng));
+
+            // Accepting without the secret, including for edge-case challenges
+            let q_minus_one =
+                group.scalar_sub(&group.scalar_from_u128(0), &group.scalar_from_u128(1));
+            for c in [
+                group.scalar_from_u128(0),
+                group.scalar_from_u128(1),
+                q_minus_one,
+            ] {
+                let challenge = Challenge { c };
+                let transcript =
+                    Transcript::simulate(group, statement.clone(), challenge, &mut rng);
+                assert!(transcript.verify(group));
+            }
+
+            let challenge = Challenge::random(group, &mut rng);
+            let transcript = Transcript::simulate(group, statement, challenge, &mut rng);
+            let encoded = transcript.encode(group);
+            assert_eq!(
+                encoded.len(),
+                4 * group.element_len() + 2 * group.scalar_len()
+            );
+            assert_eq!(Transcript::decode(group, &encoded), Ok(transcript));
+        }
+
+        check(&*consts::PARAMS);
+        #[cfg(feature = "ristretto")]
+        check(&*consts::RISTRETTO255);
+
+        // Written out, then checked again with nothing but the parameters
+        let params = &*consts::PARAMS;
+        let mut rng = random::rng();
+        let (y1, y2) = params.obfuscate(&string::as_biguint("oppenheimer"));
+        let c = random::biguint(&params.Q);
+        let ((r1, r2), s) = params.simulate((&y1, &y2), &c, &mut rng);
+        assert!(params.verify((&y1, &y2), (&r1, &r2), &c, &s));
+
+        let transcript = Transcript {
+            statement: Statement { y1, y2 },
+            commitment: proof::Commitment { r1, r2 },
+            challenge: Challenge { c },
+            response: proof::Response { s },
+        };
+        let mut encoded = transcript.encode(params);
+        assert_eq!(params.verify_transcript(&encoded), Ok(true));
+
+        // The last byte belongs to s, which still decodes but no longer answers c
+        *encoded.last_mut().unwrap() ^= 1;
+        assert_eq!(params.verify_transcript(&encoded), Ok(false));
+        assert_eq!(
+            params.verify_transcript(&encoded[1..]),
+            Err(DecodeError::InvalidLength {
+                expected: encoded.len(),
+                actual: encoded.len() - 1
+            })
+        );
+    }
+
+    #[test]
+    fn batch_verification() {
+        fn check<G: Group>(group: &G) {
+            use proof::{Challenge, Statement, Transcript};
+            use protocol::{Prover, Verifier};
+
+            let mut rng = random::rng();
+            let mut transcripts: Vec<Transcript<G>> = (0..20)
+                .map(|i| {
+                    let x = group.random_scalar(&mut rng);
+                    let (prover, commitment) = Prover::commit_random(group, &mut rng);
+                    let (verifier, challenge) = Verifier::new(Statement::new(group, &x))
+                        .challenge(group, commitment, "peggy", b"", &mut rng);
+                    let response = prover.respond(group, &challenge, &x);
+                    // Non-interactive proofs batch alongside interactive ones
+                    if i % 2 == 0 {
+                        verifier.transcript(response)
+                    } else {
+                        let (prover, _) = Prover::commit_random(group, &mut rng);
+                        let proof = prover.prove(group, &x, "peggy", b"batch");
+                        Verifier::new(Statement::new(group, &x))
+                            .proof_transcript(group, proof, "peggy", b"batch")
+                    }
+                })
+                .collect();
+            assert!(transcripts.iter().all(|t| t.verify(group)));
+            assert_eq!(proof::verify_batch(group, &transcripts, &mut rng), Ok(()));
+            assert_eq!(proof::verify_batch(group, &[], &mut rng), Ok(()));
+
+            // Failures are isolated however they're spread out
+            transcripts[3].challenge = Challenge::random(group, &mut rng);
+            transcripts[4].response.s =
+                group.scalar_add(&transcripts[4].response.s, &group.scalar_from_u128(1));
+            transcripts[17].statement = transcripts[16].statement.clone();
+            assert_eq!(
+                proof::verify_batch(group, &transcripts, &mut rng),
+                Err(vec![3, 4, 17])
+            );
+            assert_eq!(
+                proof::verify_batch(group, &transcripts[3..4], &mut rng),
+                Err(vec![0])
+            );
+
+            // Multi-exponentiation agrees with exponentiating term by term
+            let bases: Vec<_> = (0..5_u8).map(|i| group.hash_to_element(&[i])).collect();
+            let exps: Vec<_> = (0..5)
+                .map(|i| match i {
+                    0 => group.scalar_from_u128(0),
+                    1 => group.scalar_from_u128(u128::MAX),
+                    _ => group.random_scalar(&mut rng),
+                })
+                .collect();
+            let terms: Vec<_> = bases.iter().zip(&exps).collect();
+            let expected = terms.iter().fold(group.identity(), |acc, (base, exp)| {
+                group.mul(&acc, &group.exp(base, exp))
+            });
+            assert_eq!(group.multi_exp_n(&terms), expected);
+            assert_eq!(group.multi_exp_n(&[]), group.identity());
+        }
+
+        check(&*consts::PARAMS);
+        #[cfg(feature = "ristretto")]
+        check(&*consts::RISTRETTO255);
+
+        // Scalars out of range are rejected without reaching the group arithmetic
+        let params = &*consts::PARAMS;
+        let (g, h) = params.generators();
+        let big = &params.Q + 1_u8;
+        assert_eq!(
+            params.verify_batch(&[((g, h), (g, h), &big, &big)], &mut random::rng()),
+            Err(vec![0])
+        );
+    }
+
+    #[test]
+    fn precomputation() {
+        fn check<G: Group + Clone>(group: &G) {
+            let precomputed = group::Precomputed::new(group.clone());
+            let (g, h) = group.generators();
+            let x = group.random_scalar(&mut random::rng());
+            let y = group.random_scalar(&mut random::rng());
+            let z = group.hash_to_element(b"precomputation");
+
+            for (a, b) in [(g, &z), (&z, h), (g, h), (&z, &z)] {
+                let expected = group.mul(&group.exp(a, &x), &group.exp(b, &y));
+                assert_eq!(group.multi_exp((a, &x), (b, &y)), expected);
+                assert_eq!(precomputed.multi_exp((a, &x), (b, &y)), expected);
+                assert_eq!(precomputed.exp(a, &x), group.exp(a, &x));
+            }
+        }
+
+        check(&Parameters::new(4_u8.into(), 23_u8.into(), 11_u8.into(), 9_u8.into()).unwrap());
+        check(&*consts::PARAMS);
+        #[cfg(feature = "ristretto")]
+        check(&*consts::RISTRETTO255);
+
+        // Exponents wider than the table are reduced first
+        let params = &*consts::PARAMS;
+        let table = params.precompute(&params.G);
+        let x = &params.Q + 5_u8;
+        assert_eq!(
+            params.exp_precomputed(&table, &x),
+            params.G.modpow(&x, &params.P)
+        );
+    }
+
+    #[test]
+    fn constant_time_arithmetic() {
+        let small = Parameters::new(4_u8.into(), 23_u8.into(), 11_u8.into(), 9_u8.into()).unwrap();
+
+        for params in [&small, &*consts::PARAMS] {
+            let (P, Q) = (&params.P, &params.Q);
+            let x = params.random_scalar(&mut random::rng());
+            let k = params.random_scalar(&mut random::rng());
+
+            assert_eq!(params.exp_secret(&params.G, &x), params.G.modpow(&x, P));
+            assert_eq!(
+                params.exp_secret(&params.H, &BigUint::from(0_u8)),
+                BigUint::one()
+            );
+            assert_eq!(params.exp_secret(&params.H, &(Q + 1_u8)), params.H);
+
+            assert_eq!(params.scalar_mul(&k, &x), (&k * &x) % Q);
+            assert_eq!(params.scalar_add(&k, &x), (&k + &x) % Q);
+            // Both sides of the wrap-around in k - c·x
+            assert_eq!(params.scalar_sub(&k, &x), (&k + Q - &x) % Q);
+            assert_eq!(params.scalar_sub(&x, &k), (&x + Q - &k) % Q);
+            assert_eq!(params.scalar_sub(&k, &k), BigUint::from(0_u8));
+            // Unreduced operands
+            assert_eq!(
+                params.scalar_mul(&(Q + 2_u8), &(Q + 3_u8)),
+                BigUint::from(6_u8) % Q
+            );
+        }
+    }
+
+    #[test]
+    fn generator_audit() {
+        assert!(consts::PARAMS.derives_h_from(consts::PARAMS_H_SEED));
+        assert!(!consts::PARAMS.derives_h_from(b"zkp-auth/modp2048/G"));
+
+        #[cfg(feature = "ristretto")]
+        assert!(consts::RISTRETTO255.derives_h_from(group::Ristretto255::H_SEED));
+
+        for &id in group::GroupId::ALL {
+            let audited = match id.resolve() {
+                group::NamedGroup::Modp(params) => params.derives_h_from(&id.h_seed()),
+                #[cfg(feature = "ristretto")]
+                group::NamedGroup::Ristretto255(group) => group.derives_h_from(&id.h_seed()),
+            };
+            assert!(audited, "{}", id);
+        }
+    }
+
+    #[test]
+    fn registry() {
+        let mut fingerprints = std::collections::HashSet::new();
+        for &id in group::GroupId::ALL {
+            assert_eq!(id.as_str().parse::<group::GroupId>().unwrap(), id);
+
+            let group = id.resolve();
+            group.validate().unwrap();
+            assert!(fingerprints.insert(group.fingerprint()), "{}", id);
+
+            // Every challenge hashes the name, so it has to tell the groups apart
+            let name = match group {
+                group::NamedGroup::Modp(params) => params.name(),
+                #[cfg(feature = "ristretto")]
+                group::NamedGroup::Ristretto255(group) => group.name(),
+            };
+            assert_eq!(name, id.as_str());
+        }
+
+        let custom = Parameters::new(4_u8.into(), 23_u8.into(), 11_u8.into(), 9_u8.into()).unwrap();
+        assert_eq!(custom.name(), "modp");
+        // Reusing a registered prime doesn't make it the registered group
+        let params = &*consts::PARAMS;
+        let custom = Parameters::new(
+            params.G.clone(),
+            params.P.clone(),
+            params.Q.clone(),
+            4_u8.into(),
+        )
+        .unwrap();
+        assert_eq!(custom.name(), "modp");
+        assert!("modp1024".parse::<group::GroupId>().is_err());
+    }
+
+    #[test]
+    fn secret_wiping() {
+        let password: secret::Password = "oppenheimer".to_string().into();
+        assert_eq!(format!("{:?}", password), "Secret([REDACTED])");
+        assert_eq!(password.expose(), "oppenheimer");
+
+        let mut x = string::as_biguint("oppenheimer");
+        secret::Wipe::wipe(&mut x);
+        assert_eq!(x, BigUint::from(0_u8));
+    }
+
+    #[test]
+    fn seeded_replay() {
+        let group = &*consts::PARAMS;
+
+        // A whole run (salt, nonce, challenge, answer) drawn from one seed
+        let run = |mut rng: random::SecureRng| {
+            let salt = rng.bytes(kdf::SALT_LEN);
+            let x = group.scalar_from_wide(&salt);
+            let k = group.random_scalar(&mut rng);
+            let c = group.random_scalar(&mut rng);
+            let s = chaum_pedersen::solve_challenge(group, &k, &c, &x);
+            (salt, chaum_pedersen::obfuscate(group, &k), c, s)
+        };
+
+        let first = run(random::SecureRng::seeded(7));
+        assert_eq!(first, run(random::SecureRng::seeded(7)));
+        assert_ne!(first, run(random::SecureRng::seeded(8)));
+        assert_ne!(first, run(random::SecureRng::os()));
+
+        assert_ne!(random::alphanumeric(32), random::alphanumeric(32));
+    }
+
+    #[test]
+    fn deterministic_nonces() {
+        let group = &*consts::PARAMS;
+        let x = string::as_biguint("oppenheimer");
+        let context = login_context(1_690_000_000);
+
+        // A fresh seeded RNG hands out the same "entropy" every time, like a broken device
+        let derive = |x, user, context: &[u8], seed| {
+            nonce::derive(
+                group,
+                x,
+                user,
+                context,
+                &mut random::SecureRng::seeded(seed),
+            )
+        };
+
+        let k = derive(&x, "peggy", &context, 0);
+        let k = k.expose();
+        assert!(group.contains_scalar(k));
+        // Wide enough to cover the whole of Q rather than a 512-bit corner of it
+        assert!(k.bits() > 2000);
+        assert_eq!(k, derive(&x, "peggy", &context, 0).expose());
+
+        // Even with broken entropy, any change to the statement changes k
+        let other = &x + 1_u8;
+        assert_ne!(k, derive(&other, "peggy", &context, 0).expose());
+        assert_ne!(k, derive(&x, "victor", &context, 0).expose());
+        assert_ne!(
+            k,
+            derive(&x, "peggy", &login_context(1_690_000_001), 0).expose()
+        );
+        // And fresh entropy changes it regardless
+        assert_ne!(k, derive(&x, "peggy", &context, 1).expose());
+
+        let (y1, y2) = consts::PARAMS.obfuscate(&x);
+        let ((r1, r2), s) = consts::PARAMS.prove(&x, k, "peggy", &context);
+        assert!(consts::PARAMS.verify_non_interactive(
+            (&y1, &y2),
+            (&r1, &r2),
+            &s,
+            "peggy",
+            &context
+        ));
+    }
+
+    #[cfg(feature = "ristretto")]
+    #[test]
+    fn ristretto_authentication() {
+        let group = &*consts::RISTRETTO255;
+        let salt = [0xA5; kdf::SALT_LEN];
+
+        // Registration
+        let x = kdf::derive_secret(group, "oppenheimer", &salt).unwrap();
+        let x = x.expose();
+        let (y1, y2) = chaum_pedersen::obfuscate(group, x);
+
+        let encoded = group.encode_element(&y1);
+        assert_eq!(encoded.len(), 32);
+        assert_eq!(group.decode_element(&encoded), Some(y1));
+
+        // Authentication attempt 1
+        let k = group.random_scalar(&mut random::rng());
+        let (r1, r2) = chaum_pedersen::obfuscate(group, &k);
+
+        let c = group.random_scalar(&mut random::rng());
+        let s = chaum_pedersen::solve_challenge(group, &k, &c, x);
+
+        assert!(chaum_pedersen::verify(
+            group,
+            (&y1, &y2),
+            (&r1, &r2),
+            &c,
+            &s
+        ));
+
+        // Authentication attempt 2: Should fail (Incorrect password)
+        let x = kdf::derive_secret(group, "barbie", &salt).unwrap();
+
+        let k = group.random_scalar(&mut random::rng());
+        let ((r1, r2), s) = chaum_pedersen::prove(group, x.expose(), &k, "peggy", LOGIN_CONTEXT);
+
+        assert!(!chaum_pedersen::verify_non_interactive(
+            group,
+            (&y1, &y2),
+            (&r1, &r2),
+            &s,
+            "peggy",
+            LOGIN_CONTEXT
+        ));
+    }
 }
@@ -0,0 +1,143 @@
+use hmac::{Hmac, Mac};
+use rand_core::{impls, CryptoRng, RngCore};
+use sha2::Sha512;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::group::Group;
+use crate::secret::Secret;
+use crate::sigma::{ChaumPedersen, SigmaProtocol};
+
+type HmacSha512 = Hmac<Sha512>;
+
+// Output size of HMAC-SHA-512
+const DIGEST_LEN: usize = 64;
+
+/// Bytes of fresh randomness mixed into every derived nonce
+pub const ENTROPY_LEN: usize = 32;
+
+/// Domain separation tag for derived nonces
+pub const NONCE_CONTEXT: &[u8] = b"zkp-auth/nonce/v1";
+
+/// A nonce `k` derived from the secret `x`, the proof's user and context, and fresh randomness
+/// (RFC 6979 § 3.6, with the randomness as additional data)
+///
+/// Everything is fed into an HMAC-DRBG keyed by `x` and `k` is sampled from its output, so it
+/// stays unpredictable as long as either `x` or the randomness is. A device with a broken RNG
+/// then still never reuses `k` across different statements. Non-interactive proofs bind their
+/// context into the challenge too, so a repeated nonce can only ever repeat the whole proof.
+/// Interactive challenges arrive after the commitment, so their `context` has to be unique.
+pub fn derive<G: Group, R: RngCore + CryptoRng>(
+    group: &G,
+    x: &G::Scalar,
+    user: &str,
+    context: &[u8],
+    rng: &mut R,
+) -> Secret<G::Scalar> {
+    derive_for::<G, ChaumPedersen, R>(group, x, user, context, rng)
+}
+
+/// [`derive`] for a witness of protocol `P`, however many secrets it's made of
+///
+/// The protocol is part of the seed, so the same secret never yields the same nonce in two protocols.
+pub fn derive_for<G: Group, P: SigmaProtocol<G>, R: RngCore + CryptoRng>(
+    group: &G,
+    x: &P::Witness,
+    user: &str,
+    context: &[u8],
+    rng: &mut R,
+) -> Secret<P::Witness> {
+    let mut entropy = Secret::new([0; ENTROPY_LEN]);
+    rng.fill_bytes(entropy.expose_mut());
+
+    let fingerprint = group.fingerprint();
+    let secrets: Vec<_> = P::scalars(x)
+        .iter()
+        .map(|x| Zeroizing::new(group.encode_scalar(x)))
+        .collect();
+
+    let mut seed: Vec<&[u8]> = vec![NONCE_CONTEXT, &fingerprint, P::ID.as_str().as_bytes()];
+    seed.extend(secrets.iter().map(|x| x.as_slice()));
+    seed.extend([user.as_bytes(), context, entropy.expose()]);
+    let mut drbg = HmacDrbg::new(&seed);
+
+    Secret::new(P::random_nonce(group, &mut drbg))
+}
+
+// HMAC_DRBG as specified in RFC 6979 § 3.2, over length-prefixed seed fields
+struct HmacDrbg {
+    k: [u8; DIGEST_LEN],
+    v: [u8; DIGEST_LEN],
+}
+
+impl HmacDrbg {
+    fn new(seed: &[&[u8]]) -> Self {
+        let mut drbg = Self {
+            k: [0x00; DIGEST_LEN],
+            v: [0x01; DIGEST_LEN],
+        };
+        drbg.update(seed);
+        drbg
+    }
+
+    // K = HMAC_K(V || 0x00 || seed), V = HMAC_K(V), then again with 0x01 if seeding
+    fn update(&mut self, seed: &[&[u8]]) {
+        for round in [0x00, 0x01] {
+            let mut mac = self.mac();
+            mac.update(&self.v);
+            mac.update(&[round]);
+            for field in seed {
+                mac.update(&(field.len() as u64).to_be_bytes());
+                mac.update(field);
+            }
+            self.k = mac.finalize().into_bytes().into();
+            self.v = self.hmac(&self.v);
+
+            if seed.is_empty() {
+                break;
+            }
+        }
+    }
+
+    fn mac(&self) -> HmacSha512 {
+        HmacSha512::new_from_slice(&self.k).expect("HMAC takes keys of any length")
+    }
+
+    fn hmac(&self, data: &[u8]) -> [u8; DIGEST_LEN] {
+        let mut mac = self.mac();
+        mac.update(data);
+        mac.finalize().into_bytes().into()
+    }
+}
+
+impl RngCore for HmacDrbg {
+    fn next_u32(&mut self) -> u32 {
+        impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        impls::next_u64_via_fill(self)
+    }
+
+    // One generate request: V = HMAC_K(V) until there's enough output, then step K and V forward
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(DIGEST_LEN) {
+            self.v = self.hmac(&self.v);
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+        self.update(&[]);
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for HmacDrbg {}
+
+impl Drop for HmacDrbg {
+    fn drop(&mut self) {
+        self.k.zeroize();
+        self.v.zeroize();
+    }
+}
@@ -0,0 +1,561 @@
+use std::fmt;
+
+use rand_core::{CryptoRng, RngCore};
+
+use crate::chaum_pedersen;
+use crate::group::Group;
+use crate::proto;
+use crate::sigma::{ChaumPedersen, SigmaProtocol};
+
+/// Why a proof object couldn't be decoded
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The named field was left out of a message
+    Missing(&'static str),
+    /// The named field is not the canonical encoding of a group element
+    InvalidElement(&'static str),
+    /// The named field is not the canonical encoding of a scalar
+    InvalidScalar(&'static str),
+    /// The input is not exactly as long as the canonical encoding
+    InvalidLength { expected: usize, actual: usize },
+    /// The named field was set, but the message's protocol has no use for it
+    Unexpected(&'static str),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Missing(field) => write!(f, "'{}' is missing", field),
+            DecodeError::InvalidElement(field) => {
+                write!(f, "'{}' is not a valid group element", field)
+            }
+            DecodeError::InvalidScalar(field) => write!(f, "'{}' is not a valid scalar", field),
+            DecodeError::InvalidLength { expected, actual } => {
+                write!(f, "expected {} bytes, got {}", expected, actual)
+            }
+            DecodeError::Unexpected(field) => {
+                write!(f, "'{}' doesn't belong in this protocol", field)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// The public statement `(y1, y2) = (G ^ x, H ^ x)` a prover claims to know `x` for
+pub struct Statement<G: Group> {
+    pub y1: G::Element,
+    pub y2: G::Element,
+}
+
+/// The prover's first message `(r1, r2) = (G ^ k, H ^ k)`
+pub struct Commitment<G: Group> {
+    pub r1: G::Element,
+    pub r2: G::Element,
+}
+
+/// The verifier's challenge `c`
+pub struct Challenge<G: Group> {
+    pub c: G::Scalar,
+}
+
+/// The prover's answer `s = k - c · x`
+pub struct Response<G: Group> {
+    pub s: G::Scalar,
+}
+
+/// A non-interactive proof in protocol `P`, whose challenge is derived from the transcript
+/// with [`SigmaProtocol::derive_challenge`] ([`Challenge::derive`] for Chaum-Pedersen)
+pub struct Proof<G: Group, P: SigmaProtocol<G> = ChaumPedersen> {
+    pub commitment: P::Commitment,
+    pub response: P::Response,
+}
+
+/// Everything a verifier checks for one proof: the statement and the whole exchange about it
+pub struct Transcript<G: Group> {
+    pub statement: Statement<G>,
+    pub commitment: Commitment<G>,
+    pub challenge: Challenge<G>,
+    pub response: Response<G>,
+}
+
+/// A protocol message with the wire form `M`, which it converts to and from with the
+/// `From` / `TryFrom` implementations below
+pub trait Message<G: Group, M>:
+    Clone + PartialEq + fmt::Debug + Send + Sync + for<'g> TryFrom<(&'g G, M), Error = DecodeError>
+{
+    fn to_proto(&self, group: &G) -> M;
+}
+
+impl<G: Group, M, T> Message<G, M> for T
+where
+    T: Clone
+        + PartialEq
+        + fmt::Debug
+        + Send
+        + Sync
+        + for<'g> TryFrom<(&'g G, M), Error = DecodeError>,
+    for<'g> M: From<(&'g G, &'g T)>,
+{
+    fn to_proto(&self, group: &G) -> M {
+        (group, self).into()
+    }
+}
+
+// Deriving these would require the group itself to implement them, not just its elements and scalars
+macro_rules! impl_common_traits {
+    ($($name:ident $(<$protocol:ident>)? { $($field:ident),+ }),+ $(,)?) => {$(
+        impl<G: Group $(, $protocol: SigmaProtocol<G>)?> Clone for $name<G $(, $protocol)?> {
+            fn clone(&self) -> Self {
+                Self { $($field: self.$field.clone()),+ }
+            }
+        }
+
+        impl<G: Group $(, $protocol: SigmaProtocol<G>)?> PartialEq for $name<G $(, $protocol)?> {
+            fn eq(&self, other: &Self) -> bool {
+                $(self.$field == other.$field)&&+
+            }
+        }
+
+        impl<G: Group $(, $protocol: SigmaProtocol<G>)?> std::fmt::Debug for $name<G $(, $protocol)?> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.debug_struct(stringify!($name))
+                    $(.field(stringify!($field), &self.$field))+
+                    .finish()
+            }
+        }
+    )+};
+}
+
+pub(crate) use impl_common_traits;
+
+impl_common_traits! {
+    Statement { y1, y2 },
+    Commitment { r1, r2 },
+    Challenge { c },
+    Response { s },
+    Proof<P> { commitment, response },
+    Transcript { statement, commitment, challenge, response },
+}
+
+impl<G: Group> Statement<G> {
+    /// The statement for the secret `x`
+    pub fn new(group: &G, x: &G::Scalar) -> Self {
+        let (y1, y2) = chaum_pedersen::obfuscate(group, x);
+        Self { y1, y2 }
+    }
+
+    /// Whether an interactive transcript proves knowledge of this statement's `x`
+    pub fn verify(
+        &self,
+        group: &G,
+        commitment: &Commitment<G>,
+        challenge: &Challenge<G>,
+        response: &Response<G>,
+    ) -> bool {
+        chaum_pedersen::verify(
+            group,
+            (&self.y1, &self.y2),
+            (&commitment.r1, &commitment.r2),
+            &challenge.c,
+            &response.s,
+        )
+    }
+
+    /// Whether `proof` proves knowledge of this statement's `x`, for `user` in `context`
+    pub fn verify_proof(&self, group: &G, proof: &Proof<G>, user: &str, context: &[u8]) -> bool {
+        let challenge = Challenge::derive(group, self, &proof.commitment, user, context);
+
+        self.verify(group, &proof.commitment, &challenge, &proof.response)
+    }
+
+    /// `y1 || y2`
+    pub fn encode(&self, group: &G) -> Vec<u8> {
+        [&self.y1, &self.y2]
+            .into_iter()
+            .flat_map(|element| group.encode_element(element))
+            .collect()
+    }
+
+    pub fn decode(group: &G, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let [y1, y2] = split(bytes, [group.element_len(); 2])?;
+
+        Ok(Self {
+            y1: decode_element(group, "y1", y1)?,
+            y2: decode_element(group, "y2", y2)?,
+        })
+    }
+}
+
+impl<G: Group> Commitment<G> {
+    /// The commitment to the nonce `k`
+    pub fn new(group: &G, k: &G::Scalar) -> Self {
+        let (r1, r2) = chaum_pedersen::obfuscate(group, k);
+        Self { r1, r2 }
+    }
+
+    /// `r1 || r2`
+    pub fn encode(&self, group: &G) -> Vec<u8> {
+        [&self.r1, &self.r2]
+            .into_iter()
+            .flat_map(|element| group.encode_element(element))
+            .collect()
+    }
+
+    pub fn decode(group: &G, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let [r1, r2] = split(bytes, [group.element_len(); 2])?;
+
+        Ok(Self {
+            r1: decode_element(group, "r1", r1)?,
+            r2: decode_element(group, "r2", r2)?,
+        })
+    }
+}
+
+impl<G: Group> Challenge<G> {
+    /// A uniformly random challenge, bound to nothing
+    pub fn random<R: RngCore + CryptoRng>(group: &G, rng: &mut R) -> Self {
+        Self {
+            c: group.random_scalar(rng),
+        }
+    }
+
+    /// The Fiat-Shamir challenge for `commitment` to `statement`, bound to `user` and `context`
+    pub fn derive(
+        group: &G,
+        statement: &Statement<G>,
+        commitment: &Commitment<G>,
+        user: &str,
+        context: &[u8],
+    ) -> Self {
+        Self {
+            c: chaum_pedersen::challenge(
+                group,
+                (&statement.y1, &statement.y2),
+                (&commitment.r1, &commitment.r2),
+                user,
+                context,
+            ),
+        }
+    }
+
+    pub fn encode(&self, group: &G) -> Vec<u8> {
+        group.encode_scalar(&self.c)
+    }
+
+    pub fn decode(group: &G, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let [c] = split(bytes, [group.scalar_len()])?;
+
+        Ok(Self {
+            c: decode_scalar(group, "c", c)?,
+        })
+    }
+}
+
+impl<G: Group> Response<G> {
+    /// The answer to `challenge` for the nonce `k` and secret `x`
+    pub fn new(group: &G, k: &G::Scalar, challenge: &Challenge<G>, x: &G::Scalar) -> Self {
+        Self {
+            s: chaum_pedersen::solve_challenge(group, k, &challenge.c, x),
+        }
+    }
+
+    pub fn encode(&self, group: &G) -> Vec<u8> {
+        group.encode_scalar(&self.s)
+    }
+
+    pub fn decode(group: &G, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let [s] = split(bytes, [group.scalar_len()])?;
+
+        Ok(Self {
+            s: decode_scalar(group, "s", s)?,
+        })
+    }
+}
+
+impl<G: Group> Proof<G> {
+    /// Prove knowledge of `x` for `user` in `context`, committing to the nonce `k`
+    pub fn new(group: &G, x: &G::Scalar, k: &G::Scalar, user: &str, context: &[u8]) -> Self {
+        let ((r1, r2), s) = chaum_pedersen::prove(group, x, k, user, context);
+
+        Self {
+            commitment: Commitment { r1, r2 },
+            response: Response { s },
+        }
+    }
+
+    /// `r1 || r2 || s`
+    pub fn encode(&self, group: &G) -> Vec<u8> {
+        [self.commitment.encode(group), self.response.encode(group)].concat()
+    }
+
+    pub fn decode(group: &G, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let [commitment, response] = split(bytes, [2 * group.element_len(), group.scalar_len()])?;
+
+        Ok(Self {
+            commitment: Commitment::decode(group, commitment)?,
+            response: Response::decode(group, response)?,
+        })
+    }
+}
+
+impl<G: Group> Transcript<G> {
+    /// The transcript of a non-interactive `proof`, with its challenge derived for `user` in `context`
+    pub fn of_proof(
+        group: &G,
+        statement: Statement<G>,
+        proof: Proof<G>,
+        user: &str,
+        context: &[u8],
+    ) -> Self {
+        let challenge = Challenge::derive(group, &statement, &proof.commitment, user, context);
+
+        Self {
+            statement,
+            commitment: proof.commitment,
+            challenge,
+            response: proof.response,
+        }
+    }
+
+    /// An accepting transcript for `statement` and `challenge`, made without the secret
+    ///
+    /// Simulated transcripts are distributed exactly like those of honest exchanges, which
+    /// is why a transcript shows nothing about the secret to anyone but the verifier who
+    /// picked the challenge before seeing the commitment.
+    pub fn simulate<R: RngCore + CryptoRng>(
+        group: &G,
+        statement: Statement<G>,
+        challenge: Challenge<G>,
+        rng: &mut R,
+    ) -> Self {
+        let ((r1, r2), s) =
+            chaum_pedersen::simulate(group, (&statement.y1, &statement.y2), &challenge.c, rng);
+
+        Self {
+            statement,
+            commitment: Commitment { r1, r2 },
+            challenge,
+            response: Response { s },
+        }
+    }
+
+    pub fn verify(&self, group: &G) -> bool {
+        self.statement
+            .verify(group, &self.commitment, &self.challenge, &self.response)
+    }
+
+    /// `y1 || y2 || r1 || r2 || c || s`, to be written out and checked again later
+    pub fn encode(&self, group: &G) -> Vec<u8> {
+        [
+            self.statement.encode(group),
+            self.commitment.encode(group),
+            self.challenge.encode(group),
+            self.response.encode(group),
+        ]
+        .concat()
+    }
+
+    pub fn decode(group: &G, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (element_len, scalar_len) = (group.element_len(), group.scalar_len());
+        let [statement, commitment, challenge, response] = split(
+            bytes,
+            [2 * element_len, 2 * element_len, scalar_len, scalar_len],
+        )?;
+
+        Ok(Self {
+            statement: Statement::decode(group, statement)?,
+            commitment: Commitment::decode(group, commitment)?,
+            challenge: Challenge::decode(group, challenge)?,
+            response: Response::decode(group, response)?,
+        })
+    }
+}
+
+/// Check many transcripts at once with random linear combinations, returning the indices
+/// of the ones that fail
+///
+/// All of them passing costs about as much as one multi-exponentiation over every element,
+/// far less than checking them one by one.
+pub fn verify_batch<G: Group, R: RngCore + CryptoRng>(
+    group: &G,
+    transcripts: &[Transcript<G>],
+    rng: &mut R,
+) -> Result<(), Vec<usize>> {
+    let entries: Vec<_> = transcripts
+        .iter()
+        .map(|transcript| {
+            let Transcript {
+                statement,
+                commitment,
+                challenge,
+                response,
+            } = transcript;
+            (
+                (&statement.y1, &statement.y2),
+                (&commitment.r1, &commitment.r2),
+                &challenge.c,
+                &response.s,
+            )
+        })
+        .collect();
+
+    chaum_pedersen::verify_batch(group, &entries, rng)
+}
+
+// Cut `bytes` into pieces of exactly the given lengths, with nothing left over
+pub(crate) fn split<const N: usize>(
+    bytes: &[u8],
+    lens: [usize; N],
+) -> Result<[&[u8]; N], DecodeError> {
+    let expected = lens.iter().sum();
+    if bytes.len() != expected {
+        return Err(DecodeError::InvalidLength {
+            expected,
+            actual: bytes.len(),
+        });
+    }
+
+    let mut rest = bytes;
+    Ok(lens.map(|len| {
+        let (piece, tail) = rest.split_at(len);
+        rest = tail;
+        piece
+    }))
+}
+
+// Fields a protocol has no use for must be left empty, so every message has one encoding
+pub(crate) fn unexpected(field: &'static str, bytes: &[u8]) -> Result<(), DecodeError> {
+    if bytes.is_empty() {
+        Ok(())
+    } else {
+        Err(DecodeError::Unexpected(field))
+    }
+}
+
+pub(crate) fn decode_element<G: Group>(
+    group: &G,
+    field: &'static str,
+    bytes: &[u8],
+) -> Result<G::Element, DecodeError> {
+    group
+        .decode_element(bytes)
+        .ok_or(DecodeError::InvalidElement(field))
+}
+
+pub(crate) fn decode_scalar<G: Group>(
+    group: &G,
+    field: &'static str,
+    bytes: &[u8],
+) -> Result<G::Scalar, DecodeError> {
+    group
+        .decode_scalar(bytes)
+        .ok_or(DecodeError::InvalidScalar(field))
+}
+
+// Conversions to and from the wire messages, which carry each field in its canonical encoding.
+// The group is needed to decode anything, so it comes along with the message.
+
+impl<G: Group> TryFrom<(&G, proto::Statement)> for Statement<G> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::Statement)) -> Result<Self, DecodeError> {
+        Ok(Self {
+            y1: decode_element(group, "y1", &message.y1)?,
+            y2: decode_element(group, "y2", &message.y2)?,
+        })
+    }
+}
+
+impl<G: Group> From<(&G, &Statement<G>)> for proto::Statement {
+    fn from((group, statement): (&G, &Statement<G>)) -> Self {
+        Self {
+            y1: group.encode_element(&statement.y1),
+            y2: group.encode_element(&statement.y2),
+        }
+    }
+}
+
+impl<G: Group> TryFrom<(&G, proto::Commitment)> for Commitment<G> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::Commitment)) -> Result<Self, DecodeError> {
+        Ok(Self {
+            r1: decode_element(group, "r1", &message.r1)?,
+            r2: decode_element(group, "r2", &message.r2)?,
+        })
+    }
+}
+
+impl<G: Group> From<(&G, &Commitment<G>)> for proto::Commitment {
+    fn from((group, commitment): (&G, &Commitment<G>)) -> Self {
+        Self {
+            r1: group.encode_element(&commitment.r1),
+            r2: group.encode_element(&commitment.r2),
+        }
+    }
+}
+
+impl<G: Group> TryFrom<(&G, proto::Challenge)> for Challenge<G> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::Challenge)) -> Result<Self, DecodeError> {
+        Ok(Self {
+            c: decode_scalar(group, "c", &message.c)?,
+        })
+    }
+}
+
+impl<G: Group> From<(&G, &Challenge<G>)> for proto::Challenge {
+    fn from((group, challenge): (&G, &Challenge<G>)) -> Self {
+        Self {
+            c: challenge.encode(group),
+        }
+    }
+}
+
+impl<G: Group> TryFrom<(&G, proto::Response)> for Response<G> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::Response)) -> Result<Self, DecodeError> {
+        unexpected("s2", &message.s2)?;
+
+        Ok(Self {
+            s: decode_scalar(group, "s", &message.s)?,
+        })
+    }
+}
+
+impl<G: Group> From<(&G, &Response<G>)> for proto::Response {
+    fn from((group, response): (&G, &Response<G>)) -> Self {
+        Self {
+            s: response.encode(group),
+            s2: Vec::new(),
+        }
+    }
+}
+
+impl<G: Group, P: SigmaProtocol<G>> TryFrom<(&G, proto::Proof)> for Proof<G, P> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::Proof)) -> Result<Self, DecodeError> {
+        let commitment = message
+            .commitment
+            .ok_or(DecodeError::Missing("commitment"))?;
+        let response = message.response.ok_or(DecodeError::Missing("response"))?;
+
+        Ok(Self {
+            commitment: (group, commitment).try_into()?,
+            response: (group, response).try_into()?,
+        })
+    }
+}
+
+impl<G: Group, P: SigmaProtocol<G>> From<(&G, &Proof<G, P>)> for proto::Proof {
+    fn from((group, proof): (&G, &Proof<G, P>)) -> Self {
+        Self {
+            commitment: Some(proof.commitment.to_proto(group)),
+            response: Some(proof.response.to_proto(group)),
+        }
+    }
+}
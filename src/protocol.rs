@@ -0,0 +1,60 @@
+use std::fmt;
+
+use crate::group::Group;
+use crate::proof::{Proof, Response, Transcript};
+use crate::sigma::{self, ChaumPedersen};
+
+// The Chaum-Pedersen login protocol, which the state machines in `sigma` run by default.
+// Only it has transcripts, so only it can be verified in batches.
+
+/// Why a verifier turned down a proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The response doesn't answer the challenge for the statement
+    Rejected,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Rejected => f.write_str("proof rejected"),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// The Chaum-Pedersen prover, committed to a nonce and waiting for a challenge
+pub type Prover<G> = sigma::Prover<G, ChaumPedersen>;
+
+/// The Chaum-Pedersen verifier for one statement, waiting for a commitment or a non-interactive proof
+pub type Verifier<G> = sigma::Verifier<G, ChaumPedersen>;
+
+/// The Chaum-Pedersen verifier, having issued a challenge and waiting for the response
+pub type ChallengedVerifier<G> = sigma::ChallengedVerifier<G, ChaumPedersen>;
+
+impl<G: Group> Verifier<G> {
+    /// The transcript a non-interactive proof for `user` in `context` stands for, to check later
+    /// (possibly as part of a batch) rather than right away
+    pub fn proof_transcript(
+        self,
+        group: &G,
+        proof: Proof<G>,
+        user: &str,
+        context: &[u8],
+    ) -> Transcript<G> {
+        Transcript::of_proof(group, self.statement, proof, user, context)
+    }
+}
+
+impl<G: Group> ChallengedVerifier<G> {
+    /// The finished exchange, to check later (possibly as part of a batch) rather than right away
+    pub fn transcript(self, response: Response<G>) -> Transcript<G> {
+        Transcript {
+            statement: self.statement,
+            commitment: self.commitment,
+            challenge: self.challenge,
+            response,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::Zero;
+use zeroize::Zeroize;
+
+/// Values whose memory can be overwritten in place
+pub trait Wipe {
+    fn wipe(&mut self);
+}
+
+/// A secret that is wiped from memory when dropped and never shows up in `Debug` output
+///
+/// The value can only be reached through [`Secret::expose`], which keeps every use of it easy to find.
+pub struct Secret<T: Wipe>(T);
+
+/// A password as typed by the user
+pub type Password = Secret<String>;
+
+impl<T: Wipe> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn expose_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T: Wipe> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<T: Wipe> Drop for Secret<T> {
+    fn drop(&mut self) {
+        self.0.wipe();
+    }
+}
+
+impl<T: Wipe> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret([REDACTED])")
+    }
+}
+
+impl Wipe for String {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl Wipe for Vec<u8> {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<const N: usize> Wipe for [u8; N] {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl<T: Wipe, const N: usize> Wipe for [T; N] {
+    fn wipe(&mut self) {
+        self.iter_mut().for_each(Wipe::wipe);
+    }
+}
+
+// `BigUint` doesn't expose its digits, so overwrite them through the public API with a
+// pattern of the same length. All-ones, because zeros would be normalized away and the
+// allocation freed, letting the compiler drop the writes as dead stores.
+impl Wipe for BigUint {
+    fn wipe(&mut self) {
+        let digits = (self.bits() as usize).div_ceil(u32::BITS as usize);
+        self.assign_from_slice(&vec![u32::MAX; digits]);
+        std::hint::black_box(&*self);
+        self.set_zero();
+    }
+}
+
+#[cfg(feature = "ristretto")]
+impl Wipe for curve25519_dalek::scalar::Scalar {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+}
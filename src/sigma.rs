@@ -0,0 +1,360 @@
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
+
+use rand_core::{CryptoRng, RngCore};
+use sha2::{Digest, Sha512};
+
+use crate::group::Group;
+use crate::proof::{Challenge, Message, Proof};
+use crate::proto;
+use crate::protocol::VerifyError;
+use crate::secret::{Secret, Wipe};
+
+mod chaum_pedersen;
+pub mod compose;
+pub mod okamoto;
+pub mod schnorr;
+
+pub use self::chaum_pedersen::ChaumPedersen;
+pub use okamoto::Okamoto;
+pub use schnorr::Schnorr;
+
+/// A three-move proof of knowledge: the prover commits to a nonce, the verifier answers with a
+/// random challenge, and the prover responds with a mix of the nonce and its witness
+///
+/// Every protocol here proves knowledge of a preimage under a homomorphism from scalars to
+/// group elements, so nonces have the same shape as witnesses and every secret `x` gets a
+/// response `s = k - c · x`. Implementors are markers; the state lives in [`Prover`] and [`Verifier`].
+pub trait SigmaProtocol<G: Group>: Send + Sync + 'static {
+    /// Stable identifier, which users register under
+    const ID: ProtocolId;
+
+    /// The secrets the prover knows, and the shape of its nonces
+    type Witness: Wipe;
+    /// The public value the prover claims to know a witness for
+    type Statement: Message<G, proto::Statement>;
+    /// The prover's first message
+    type Commitment: Message<G, proto::Commitment>;
+    /// The prover's answer to the challenge
+    type Response: Message<G, proto::Response>;
+
+    /// The scalars making up `x`, in order
+    fn scalars(x: &Self::Witness) -> &[G::Scalar];
+
+    /// The statement for the witness `x`
+    fn statement(group: &G, x: &Self::Witness) -> Self::Statement;
+
+    /// A uniformly random nonce
+    fn random_nonce<R: RngCore + CryptoRng>(group: &G, rng: &mut R) -> Self::Witness;
+
+    /// The commitment to the nonce `k`
+    fn commit(group: &G, k: &Self::Witness) -> Self::Commitment;
+
+    /// A fresh challenge, as an interactive verifier picks it: the Fiat-Shamir challenge with
+    /// fresh randomness mixed into the context, so it's just as bound to `user` and `context`
+    /// but the prover can't predict it
+    fn challenge<R: RngCore + CryptoRng>(
+        group: &G,
+        statement: &Self::Statement,
+        commitment: &Self::Commitment,
+        user: &str,
+        context: &[u8],
+        rng: &mut R,
+    ) -> Challenge<G> {
+        let mut seed = [0; 32];
+        rng.fill_bytes(&mut seed);
+
+        Self::derive_challenge(
+            group,
+            statement,
+            commitment,
+            user,
+            &[context, &seed].concat(),
+        )
+    }
+
+    /// The answer to `challenge` for the nonce `k` and witness `x`
+    fn respond(
+        group: &G,
+        k: &Self::Witness,
+        challenge: &Challenge<G>,
+        x: &Self::Witness,
+    ) -> Self::Response;
+
+    /// Whether the transcript proves knowledge of a witness for `statement`
+    ///
+    /// Elements are trusted to have come through `Group::decode_element`, but scalars are range checked.
+    fn verify(
+        group: &G,
+        statement: &Self::Statement,
+        commitment: &Self::Commitment,
+        challenge: &Challenge<G>,
+        response: &Self::Response,
+    ) -> bool;
+
+    /// The Fiat-Shamir challenge for `commitment` to `statement`, bound to `user` and `context`
+    fn derive_challenge(
+        group: &G,
+        statement: &Self::Statement,
+        commitment: &Self::Commitment,
+        user: &str,
+        context: &[u8],
+    ) -> Challenge<G>;
+}
+
+/// Stable identifiers for the protocols a user can register with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolId {
+    /// Knowledge of `x` with `(y1, y2) = (G ^ x, H ^ x)`
+    ChaumPedersen,
+    /// Knowledge of `x` with `y = G ^ x`
+    Schnorr,
+    /// Knowledge of `(x1, x2)` with `y = G ^ x1 · H ^ x2`
+    Okamoto,
+}
+
+impl ProtocolId {
+    pub const ALL: &'static [ProtocolId] = &[
+        ProtocolId::ChaumPedersen,
+        ProtocolId::Schnorr,
+        ProtocolId::Okamoto,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ProtocolId::ChaumPedersen => "chaum-pedersen",
+            ProtocolId::Schnorr => "schnorr",
+            ProtocolId::Okamoto => "okamoto",
+        }
+    }
+}
+
+impl Display for ProtocolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug)]
+pub struct UnknownProtocol(String);
+
+impl Display for UnknownProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown protocol '{}', expected one of: ", self.0)?;
+        for (i, id) in ProtocolId::ALL.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(f, "'{}'", id)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnknownProtocol {}
+
+impl FromStr for ProtocolId {
+    type Err = UnknownProtocol;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ProtocolId::ALL
+            .iter()
+            .find(|id| id.as_str() == s)
+            .copied()
+            .ok_or_else(|| UnknownProtocol(s.to_string()))
+    }
+}
+
+// c = SHA-512(group, [label], elements, user, context) mod q
+// Every field is length-prefixed so that no two transcripts hash the same bytes. The label names
+// the protocol (or composition); Chaum-Pedersen transcripts predate the others and leave it out.
+pub(crate) fn transcript_challenge<G: Group>(
+    group: &G,
+    label: Option<&str>,
+    elements: &[&G::Element],
+    user: &str,
+    context: &[u8],
+) -> G::Scalar {
+    let mut hasher = Sha512::new();
+    let mut absorb = |field: &[u8]| {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field);
+    };
+
+    absorb(group.name().as_bytes());
+    for parameter in group.parameters() {
+        absorb(&parameter);
+    }
+    if let Some(label) = label {
+        absorb(label.as_bytes());
+    }
+    for element in elements {
+        absorb(&group.encode_element(element));
+    }
+    absorb(user.as_bytes());
+    absorb(context);
+
+    group.scalar_from_wide(&hasher.finalize())
+}
+
+// The protocol as state machines over the typed messages, with no IO of their own. Each step
+// consumes the state it starts from, so steps can't be skipped or repeated: in particular a
+// prover can't answer two challenges with the same nonce.
+
+/// The prover in protocol `P`, committed to a nonce and waiting for a challenge
+pub struct Prover<G: Group, P: SigmaProtocol<G>> {
+    k: Secret<P::Witness>,
+    commitment: P::Commitment,
+}
+
+impl<G: Group, P: SigmaProtocol<G>> Prover<G, P> {
+    /// Commit to the nonce `k`, returning the commitment to send to the verifier
+    pub fn commit(group: &G, k: Secret<P::Witness>) -> (Self, P::Commitment) {
+        let commitment = P::commit(group, k.expose());
+
+        (
+            Self {
+                k,
+                commitment: commitment.clone(),
+            },
+            commitment,
+        )
+    }
+
+    /// Commit to a fresh random nonce
+    pub fn commit_random<R: RngCore + CryptoRng>(group: &G, rng: &mut R) -> (Self, P::Commitment) {
+        Self::commit(group, Secret::new(P::random_nonce(group, rng)))
+    }
+
+    /// Answer the verifier's challenge with the witness `x`, using up the nonce
+    pub fn respond(self, group: &G, challenge: &Challenge<G>, x: &P::Witness) -> P::Response {
+        P::respond(group, self.k.expose(), challenge, x)
+    }
+
+    /// Answer a challenge derived from the transcript instead, for `user` in `context`
+    pub fn prove(self, group: &G, x: &P::Witness, user: &str, context: &[u8]) -> Proof<G, P> {
+        let statement = P::statement(group, x);
+        let challenge = P::derive_challenge(group, &statement, &self.commitment, user, context);
+        let commitment = self.commitment.clone();
+
+        Proof {
+            commitment,
+            response: self.respond(group, &challenge, x),
+        }
+    }
+}
+
+/// The verifier in protocol `P` for one statement, waiting for a commitment or a non-interactive proof
+pub struct Verifier<G: Group, P: SigmaProtocol<G>> {
+    pub(crate) statement: P::Statement,
+}
+
+/// The verifier, having issued a challenge and waiting for the response
+pub struct ChallengedVerifier<G: Group, P: SigmaProtocol<G>> {
+    pub(crate) statement: P::Statement,
+    pub(crate) commitment: P::Commitment,
+    pub(crate) challenge: Challenge<G>,
+}
+
+impl<G: Group, P: SigmaProtocol<G>> Verifier<G, P> {
+    pub fn new(statement: P::Statement) -> Self {
+        Self { statement }
+    }
+
+    /// Answer the prover's commitment with a fresh challenge, bound to `user` in `context`
+    pub fn challenge<R: RngCore + CryptoRng>(
+        self,
+        group: &G,
+        commitment: P::Commitment,
+        user: &str,
+        context: &[u8],
+        rng: &mut R,
+    ) -> (ChallengedVerifier<G, P>, Challenge<G>) {
+        let challenge = P::challenge(group, &self.statement, &commitment, user, context, rng);
+
+        (
+            ChallengedVerifier {
+                statement: self.statement,
+                commitment,
+                challenge: challenge.clone(),
+            },
+            challenge,
+        )
+    }
+
+    /// Check a non-interactive proof made for `user` in `context`
+    pub fn verify_proof(
+        &self,
+        group: &G,
+        proof: &Proof<G, P>,
+        user: &str,
+        context: &[u8],
+    ) -> Result<(), VerifyError> {
+        let challenge =
+            P::derive_challenge(group, &self.statement, &proof.commitment, user, context);
+
+        if P::verify(
+            group,
+            &self.statement,
+            &proof.commitment,
+            &challenge,
+            &proof.response,
+        ) {
+            Ok(())
+        } else {
+            Err(VerifyError::Rejected)
+        }
+    }
+}
+
+impl<G: Group, P: SigmaProtocol<G>> ChallengedVerifier<G, P> {
+    /// Pick an exchange back up from the statement, the prover's commitment and the challenge
+    /// issued for it, say after keeping them in storage while the prover works on its response
+    pub fn resume(
+        statement: P::Statement,
+        commitment: P::Commitment,
+        challenge: Challenge<G>,
+    ) -> Self {
+        Self {
+            statement,
+            commitment,
+            challenge,
+        }
+    }
+
+    /// The commitment the challenge was issued for
+    pub fn commitment(&self) -> &P::Commitment {
+        &self.commitment
+    }
+
+    /// The challenge that was issued
+    pub fn challenge(&self) -> &Challenge<G> {
+        &self.challenge
+    }
+
+    /// Check the prover's response, which ends the exchange either way
+    pub fn verify(self, group: &G, response: &P::Response) -> Result<(), VerifyError> {
+        if P::verify(
+            group,
+            &self.statement,
+            &self.commitment,
+            &self.challenge,
+            response,
+        ) {
+            Ok(())
+        } else {
+            Err(VerifyError::Rejected)
+        }
+    }
+}
+
+impl<G: Group, P: SigmaProtocol<G>> Debug for ChallengedVerifier<G, P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChallengedVerifier")
+            .field("protocol", &P::ID)
+            .field("statement", &self.statement)
+            .field("commitment", &self.commitment)
+            .field("challenge", &self.challenge)
+            .finish()
+    }
+}
@@ -0,0 +1,61 @@
+use rand_core::{CryptoRng, RngCore};
+
+use super::{ProtocolId, SigmaProtocol};
+use crate::group::Group;
+use crate::proof::{Challenge, Commitment, Response, Statement};
+
+/// Chaum-Pedersen's proof of knowledge of `x` with `(y1, y2) = (G ^ x, H ^ x)`, which also
+/// shows that both elements share the same discrete log
+///
+/// The messages are the ones in [`crate::proof`], and the arithmetic is in [`crate::chaum_pedersen`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChaumPedersen;
+
+impl<G: Group> SigmaProtocol<G> for ChaumPedersen {
+    const ID: ProtocolId = ProtocolId::ChaumPedersen;
+
+    type Witness = G::Scalar;
+    type Statement = Statement<G>;
+    type Commitment = Commitment<G>;
+    type Response = Response<G>;
+
+    fn scalars(x: &G::Scalar) -> &[G::Scalar] {
+        std::slice::from_ref(x)
+    }
+
+    fn statement(group: &G, x: &G::Scalar) -> Statement<G> {
+        Statement::new(group, x)
+    }
+
+    fn random_nonce<R: RngCore + CryptoRng>(group: &G, rng: &mut R) -> G::Scalar {
+        group.random_scalar(rng)
+    }
+
+    fn commit(group: &G, k: &G::Scalar) -> Commitment<G> {
+        Commitment::new(group, k)
+    }
+
+    fn respond(group: &G, k: &G::Scalar, challenge: &Challenge<G>, x: &G::Scalar) -> Response<G> {
+        Response::new(group, k, challenge, x)
+    }
+
+    fn verify(
+        group: &G,
+        statement: &Statement<G>,
+        commitment: &Commitment<G>,
+        challenge: &Challenge<G>,
+        response: &Response<G>,
+    ) -> bool {
+        statement.verify(group, commitment, challenge, response)
+    }
+
+    fn derive_challenge(
+        group: &G,
+        statement: &Statement<G>,
+        commitment: &Commitment<G>,
+        user: &str,
+        context: &[u8],
+    ) -> Challenge<G> {
+        Challenge::derive(group, statement, commitment, user, context)
+    }
+}
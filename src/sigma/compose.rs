@@ -0,0 +1,395 @@
+use rand_core::{CryptoRng, RngCore};
+use subtle::{Choice, ConstantTimeEq};
+
+use super::transcript_challenge;
+use crate::group::Group;
+use crate::proof::{
+    impl_common_traits, Challenge, Commitment, DecodeError, Message, Response, Statement,
+    Transcript,
+};
+use crate::proto;
+use crate::protocol::VerifyError;
+use crate::secret::Secret;
+
+// Compositions of Chaum-Pedersen proofs over several statements, all non-interactive. Both
+// derive one challenge from every statement and commitment, so no branch can be swapped out
+// or reordered: AND answers it in every branch, and OR (Cramer-Damgård-Schoenmakers) splits it
+// into one challenge per branch, so that the prover can simulate every branch but the one it
+// knows the secret for.
+
+/// Proof of knowledge of the secrets behind every one of several statements
+pub struct AndProof<G: Group> {
+    pub commitments: Vec<Commitment<G>>,
+    pub responses: Vec<Response<G>>,
+}
+
+/// Proof of knowledge of the secret behind one of several statements, which doesn't
+/// reveal which one
+pub struct OrProof<G: Group> {
+    pub commitments: Vec<Commitment<G>>,
+    pub challenges: Vec<Challenge<G>>,
+    pub responses: Vec<Response<G>>,
+}
+
+impl_common_traits! {
+    AndProof { commitments, responses },
+    OrProof { commitments, challenges, responses },
+}
+
+// 1 for the real branch of an OR proof and 0 for the others, as a scalar so that picking
+// between them is arithmetic rather than a branch
+fn flag<G: Group>(group: &G, real: Choice) -> G::Scalar {
+    group.scalar_from_u128(real.unwrap_u8().into())
+}
+
+// `a` if `flag` is 1 and `b` if it's 0, computed as `b + flag · (a - b)`
+fn pick<G: Group>(group: &G, flag: &G::Scalar, a: &G::Scalar, b: &G::Scalar) -> G::Scalar {
+    group.scalar_add(b, &group.scalar_mul(flag, &group.scalar_sub(a, b)))
+}
+
+const AND_LABEL: &str = "chaum-pedersen-and";
+const OR_LABEL: &str = "chaum-pedersen-or";
+
+// Membership proofs are OR proofs that name no user, only the ring
+const ANONYMOUS: &str = "";
+
+// c = SHA-512(group, label, y1, y2, ..., r1, r2, ..., user, context) mod q
+fn derive_challenge<G: Group>(
+    group: &G,
+    label: &str,
+    statements: &[Statement<G>],
+    commitments: &[Commitment<G>],
+    user: &str,
+    context: &[u8],
+) -> Challenge<G> {
+    let elements: Vec<_> = statements
+        .iter()
+        .flat_map(|statement| [&statement.y1, &statement.y2])
+        .chain(
+            commitments
+                .iter()
+                .flat_map(|commitment| [&commitment.r1, &commitment.r2]),
+        )
+        .collect();
+
+    Challenge {
+        c: transcript_challenge(group, Some(label), &elements, user, context),
+    }
+}
+
+impl<G: Group> AndProof<G> {
+    /// Prove knowledge of every secret in `xs`, in the order of their statements, for `user`
+    /// in `context`
+    pub fn new<R: RngCore + CryptoRng>(
+        group: &G,
+        xs: &[G::Scalar],
+        user: &str,
+        context: &[u8],
+        rng: &mut R,
+    ) -> Self {
+        let statements: Vec<_> = xs.iter().map(|x| Statement::new(group, x)).collect();
+        let nonces: Vec<_> = xs
+            .iter()
+            .map(|_| Secret::new(group.random_scalar(rng)))
+            .collect();
+        let commitments: Vec<_> = nonces
+            .iter()
+            .map(|k| Commitment::new(group, k.expose()))
+            .collect();
+
+        let challenge =
+            derive_challenge(group, AND_LABEL, &statements, &commitments, user, context);
+        let responses = nonces
+            .iter()
+            .zip(xs)
+            .map(|(k, x)| Response::new(group, k.expose(), &challenge, x))
+            .collect();
+
+        Self {
+            commitments,
+            responses,
+        }
+    }
+
+    /// The branches as transcripts over `statements`, to check later (possibly in a batch)
+    /// rather than right away. `None` if there isn't exactly one branch per statement.
+    pub fn transcripts(
+        &self,
+        group: &G,
+        statements: &[Statement<G>],
+        user: &str,
+        context: &[u8],
+    ) -> Option<Vec<Transcript<G>>> {
+        let n = statements.len();
+        if n == 0 || self.commitments.len() != n || self.responses.len() != n {
+            return None;
+        }
+
+        let challenge = derive_challenge(
+            group,
+            AND_LABEL,
+            statements,
+            &self.commitments,
+            user,
+            context,
+        );
+
+        Some(
+            statements
+                .iter()
+                .zip(&self.commitments)
+                .zip(&self.responses)
+                .map(|((statement, commitment), response)| Transcript {
+                    statement: statement.clone(),
+                    commitment: commitment.clone(),
+                    challenge: challenge.clone(),
+                    response: response.clone(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Check the proof against `statements`, made for `user` in `context`
+    pub fn verify(
+        &self,
+        group: &G,
+        statements: &[Statement<G>],
+        user: &str,
+        context: &[u8],
+    ) -> Result<(), VerifyError> {
+        let transcripts = self
+            .transcripts(group, statements, user, context)
+            .ok_or(VerifyError::Rejected)?;
+
+        if transcripts
+            .iter()
+            .all(|transcript| transcript.verify(group))
+        {
+            Ok(())
+        } else {
+            Err(VerifyError::Rejected)
+        }
+    }
+}
+
+impl<G: Group> OrProof<G> {
+    /// Prove knowledge of `x`, the secret behind `statements[index]`, for `user` in `context`
+    ///
+    /// Panics if `index` is out of range.
+    pub fn new<R: RngCore + CryptoRng>(
+        group: &G,
+        statements: &[Statement<G>],
+        index: usize,
+        x: &G::Scalar,
+        user: &str,
+        context: &[u8],
+        rng: &mut R,
+    ) -> Self {
+        assert!(index < statements.len(), "no statement at index {}", index);
+
+        // Every branch does the same work, whichever is real, so that timing doesn't give it
+        // away. Each commits to `G^a · y1^b` (and `H^a · y2^b`): the real one to its nonce, with
+        // `(a, b) = (k, 0)`, and the others to a transcript simulated for a random challenge and
+        // response, with `(a, b) = (s, c)`.
+        let zero = group.scalar_from_u128(0);
+        let (g, h) = group.generators();
+        let n = statements.len();
+        let (mut reals, mut nonces, mut simulated, mut commitments) = (
+            Vec::with_capacity(n),
+            Vec::with_capacity(n),
+            Vec::with_capacity(n),
+            Vec::with_capacity(n),
+        );
+        for (i, statement) in statements.iter().enumerate() {
+            let real = flag(group, (i as u64).ct_eq(&(index as u64)));
+            let k = Secret::new(group.random_scalar(rng));
+            let (c, s) = (group.random_scalar(rng), group.random_scalar(rng));
+
+            let a = Secret::new(pick(group, &real, k.expose(), &s));
+            let b = Secret::new(pick(group, &real, &zero, &c));
+            commitments.push(Commitment {
+                r1: group.mul(
+                    &group.exp_secret(g, a.expose()),
+                    &group.exp_secret(&statement.y1, b.expose()),
+                ),
+                r2: group.mul(
+                    &group.exp_secret(h, a.expose()),
+                    &group.exp_secret(&statement.y2, b.expose()),
+                ),
+            });
+
+            reals.push(real);
+            nonces.push(k);
+            simulated.push((c, s));
+        }
+
+        // The real branch gets whatever is left of the challenge once the others take theirs
+        let challenge = derive_challenge(group, OR_LABEL, statements, &commitments, user, context);
+        let rest = reals
+            .iter()
+            .zip(&simulated)
+            .fold(challenge.c, |rest, (real, (c, _))| {
+                group.scalar_sub(&rest, &pick(group, real, &zero, c))
+            });
+
+        let (challenges, responses) = reals
+            .iter()
+            .zip(&nonces)
+            .zip(simulated)
+            .map(|((real, k), (c, s))| {
+                let challenge = Challenge {
+                    c: pick(group, real, &rest, &c),
+                };
+                let answer = Response::new(group, k.expose(), &challenge, x);
+                let response = Response {
+                    s: pick(group, real, &answer.s, &s),
+                };
+
+                (challenge, response)
+            })
+            .unzip();
+
+        Self {
+            commitments,
+            challenges,
+            responses,
+        }
+    }
+
+    /// Prove membership of `ring`, knowing `x`, the secret behind `ring[index]`, without
+    /// revealing which member made the proof
+    ///
+    /// Panics if `index` is out of range.
+    pub fn membership<R: RngCore + CryptoRng>(
+        group: &G,
+        ring: &[Statement<G>],
+        index: usize,
+        x: &G::Scalar,
+        context: &[u8],
+        rng: &mut R,
+    ) -> Self {
+        Self::new(group, ring, index, x, ANONYMOUS, context, rng)
+    }
+
+    /// Check a proof of membership of `ring`, made in `context`
+    pub fn verify_membership(
+        &self,
+        group: &G,
+        ring: &[Statement<G>],
+        context: &[u8],
+    ) -> Result<(), VerifyError> {
+        self.verify(group, ring, ANONYMOUS, context)
+    }
+
+    /// Check the proof against `statements`, made for `user` in `context`
+    pub fn verify(
+        &self,
+        group: &G,
+        statements: &[Statement<G>],
+        user: &str,
+        context: &[u8],
+    ) -> Result<(), VerifyError> {
+        let n = statements.len();
+        if n == 0
+            || self.commitments.len() != n
+            || self.challenges.len() != n
+            || self.responses.len() != n
+            || !self.challenges.iter().all(|c| group.contains_scalar(&c.c))
+        {
+            return Err(VerifyError::Rejected);
+        }
+
+        let challenge = derive_challenge(
+            group,
+            OR_LABEL,
+            statements,
+            &self.commitments,
+            user,
+            context,
+        );
+        let sum = self
+            .challenges
+            .iter()
+            .fold(group.scalar_from_u128(0), |sum, c| {
+                group.scalar_add(&sum, &c.c)
+            });
+        if sum != challenge.c {
+            return Err(VerifyError::Rejected);
+        }
+
+        let holds = statements
+            .iter()
+            .zip(&self.commitments)
+            .zip(self.challenges.iter().zip(&self.responses))
+            .all(|((statement, commitment), (challenge, response))| {
+                statement.verify(group, commitment, challenge, response)
+            });
+
+        if holds {
+            Ok(())
+        } else {
+            Err(VerifyError::Rejected)
+        }
+    }
+}
+
+// The lengths of the repeated fields are left for `verify` to check against the statements
+fn decode_all<G: Group, M, T>(group: &G, messages: Vec<M>) -> Result<Vec<T>, DecodeError>
+where
+    T: for<'g> TryFrom<(&'g G, M), Error = DecodeError>,
+{
+    messages
+        .into_iter()
+        .map(|message| T::try_from((group, message)))
+        .collect()
+}
+
+impl<G: Group> TryFrom<(&G, proto::AndProof)> for AndProof<G> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::AndProof)) -> Result<Self, DecodeError> {
+        Ok(Self {
+            commitments: decode_all(group, message.commitments)?,
+            responses: decode_all(group, message.responses)?,
+        })
+    }
+}
+
+impl<G: Group> From<(&G, &AndProof<G>)> for proto::AndProof {
+    fn from((group, proof): (&G, &AndProof<G>)) -> Self {
+        Self {
+            commitments: proof
+                .commitments
+                .iter()
+                .map(|r| r.to_proto(group))
+                .collect(),
+            responses: proof.responses.iter().map(|s| s.to_proto(group)).collect(),
+        }
+    }
+}
+
+impl<G: Group> TryFrom<(&G, proto::OrProof)> for OrProof<G> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::OrProof)) -> Result<Self, DecodeError> {
+        Ok(Self {
+            commitments: decode_all(group, message.commitments)?,
+            challenges: decode_all(group, message.challenges)?,
+            responses: decode_all(group, message.responses)?,
+        })
+    }
+}
+
+impl<G: Group> From<(&G, &OrProof<G>)> for proto::OrProof {
+    fn from((group, proof): (&G, &OrProof<G>)) -> Self {
+        Self {
+            commitments: proof
+                .commitments
+                .iter()
+                .map(|r| r.to_proto(group))
+                .collect(),
+            challenges: proof.challenges.iter().map(|c| c.to_proto(group)).collect(),
+            responses: proof.responses.iter().map(|s| s.to_proto(group)).collect(),
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use rand_core::{CryptoRng, RngCore};
+
+use super::{transcript_challenge, ProtocolId, SigmaProtocol};
+use crate::chaum_pedersen;
+use crate::group::Group;
+use crate::proof::{
+    decode_element, decode_scalar, impl_common_traits, split, unexpected, Challenge, DecodeError,
+};
+use crate::proto;
+
+/// Okamoto's proof of knowledge of a representation `(x1, x2)` of `y = G ^ x1 · H ^ x2`
+///
+/// This is what credentials made of several secrets are proven with. It relies on nobody
+/// knowing `log_G(H)`, which hashing `H` into the group guarantees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Okamoto;
+
+/// The public statement `y = G ^ x1 · H ^ x2`
+pub struct Statement<G: Group> {
+    pub y: G::Element,
+}
+
+/// The prover's first message `r = G ^ k1 · H ^ k2`
+pub struct Commitment<G: Group> {
+    pub r: G::Element,
+}
+
+/// The prover's answer `(s1, s2) = (k1 - c · x1, k2 - c · x2)`
+pub struct Response<G: Group> {
+    pub s1: G::Scalar,
+    pub s2: G::Scalar,
+}
+
+impl_common_traits! {
+    Statement { y },
+    Commitment { r },
+    Response { s1, s2 },
+}
+
+// G ^ x1 · H ^ x2, for secrets or nonces, so it takes the constant-time path
+fn represent<G: Group>(group: &G, [x1, x2]: &[G::Scalar; 2]) -> G::Element {
+    let (g, h) = group.generators();
+
+    group.mul(&group.exp_secret(g, x1), &group.exp_secret(h, x2))
+}
+
+impl<G: Group> SigmaProtocol<G> for Okamoto {
+    const ID: ProtocolId = ProtocolId::Okamoto;
+
+    type Witness = [G::Scalar; 2];
+    type Statement = Statement<G>;
+    type Commitment = Commitment<G>;
+    type Response = Response<G>;
+
+    fn scalars(x: &[G::Scalar; 2]) -> &[G::Scalar] {
+        x
+    }
+
+    fn statement(group: &G, x: &[G::Scalar; 2]) -> Statement<G> {
+        Statement {
+            y: represent(group, x),
+        }
+    }
+
+    fn random_nonce<R: RngCore + CryptoRng>(group: &G, rng: &mut R) -> [G::Scalar; 2] {
+        [group.random_scalar(rng), group.random_scalar(rng)]
+    }
+
+    fn commit(group: &G, k: &[G::Scalar; 2]) -> Commitment<G> {
+        Commitment {
+            r: represent(group, k),
+        }
+    }
+
+    // s1 = (k1 - (c * x1)) mod q
+    // s2 = (k2 - (c * x2)) mod q
+    fn respond(
+        group: &G,
+        [k1, k2]: &[G::Scalar; 2],
+        challenge: &Challenge<G>,
+        [x1, x2]: &[G::Scalar; 2],
+    ) -> Response<G> {
+        Response {
+            s1: chaum_pedersen::solve_challenge(group, k1, &challenge.c, x1),
+            s2: chaum_pedersen::solve_challenge(group, k2, &challenge.c, x2),
+        }
+    }
+
+    // r ⇔ v = (G ^ s1) · (H ^ s2) · (y ^ c)
+    fn verify(
+        group: &G,
+        statement: &Statement<G>,
+        commitment: &Commitment<G>,
+        challenge: &Challenge<G>,
+        response: &Response<G>,
+    ) -> bool {
+        let (c, s1, s2) = (&challenge.c, &response.s1, &response.s2);
+        if ![c, s1, s2].into_iter().all(|s| group.contains_scalar(s)) {
+            return false;
+        }
+
+        let (g, h) = group.generators();
+
+        commitment.r == group.multi_exp_n(&[(g, s1), (h, s2), (&statement.y, c)])
+    }
+
+    // c = SHA-512(group, "okamoto", y, r, user, context) mod q
+    fn derive_challenge(
+        group: &G,
+        statement: &Statement<G>,
+        commitment: &Commitment<G>,
+        user: &str,
+        context: &[u8],
+    ) -> Challenge<G> {
+        Challenge {
+            c: transcript_challenge(
+                group,
+                Some(ProtocolId::Okamoto.as_str()),
+                &[&statement.y, &commitment.r],
+                user,
+                context,
+            ),
+        }
+    }
+}
+
+impl<G: Group> Statement<G> {
+    /// The statement for the secrets `(x1, x2)`
+    pub fn new(group: &G, x: &[G::Scalar; 2]) -> Self {
+        Okamoto::statement(group, x)
+    }
+
+    /// `y`
+    pub fn encode(&self, group: &G) -> Vec<u8> {
+        group.encode_element(&self.y)
+    }
+
+    pub fn decode(group: &G, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let [y] = split(bytes, [group.element_len()])?;
+
+        Ok(Self {
+            y: decode_element(group, "y", y)?,
+        })
+    }
+}
+
+impl<G: Group> Commitment<G> {
+    /// `r`
+    pub fn encode(&self, group: &G) -> Vec<u8> {
+        group.encode_element(&self.r)
+    }
+
+    pub fn decode(group: &G, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let [r] = split(bytes, [group.element_len()])?;
+
+        Ok(Self {
+            r: decode_element(group, "r", r)?,
+        })
+    }
+}
+
+impl<G: Group> Response<G> {
+    /// `s1 || s2`
+    pub fn encode(&self, group: &G) -> Vec<u8> {
+        [&self.s1, &self.s2]
+            .into_iter()
+            .flat_map(|scalar| group.encode_scalar(scalar))
+            .collect()
+    }
+
+    pub fn decode(group: &G, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let [s1, s2] = split(bytes, [group.scalar_len(); 2])?;
+
+        Ok(Self {
+            s1: decode_scalar(group, "s1", s1)?,
+            s2: decode_scalar(group, "s2", s2)?,
+        })
+    }
+}
+
+// On the wire `y`, `r` and `s1` travel as `y1`, `r1` and `s`
+
+impl<G: Group> TryFrom<(&G, proto::Statement)> for Statement<G> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::Statement)) -> Result<Self, DecodeError> {
+        unexpected("y2", &message.y2)?;
+
+        Ok(Self {
+            y: decode_element(group, "y1", &message.y1)?,
+        })
+    }
+}
+
+impl<G: Group> From<(&G, &Statement<G>)> for proto::Statement {
+    fn from((group, statement): (&G, &Statement<G>)) -> Self {
+        Self {
+            y1: statement.encode(group),
+            y2: Vec::new(),
+        }
+    }
+}
+
+impl<G: Group> TryFrom<(&G, proto::Commitment)> for Commitment<G> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::Commitment)) -> Result<Self, DecodeError> {
+        unexpected("r2", &message.r2)?;
+
+        Ok(Self {
+            r: decode_element(group, "r1", &message.r1)?,
+        })
+    }
+}
+
+impl<G: Group> From<(&G, &Commitment<G>)> for proto::Commitment {
+    fn from((group, commitment): (&G, &Commitment<G>)) -> Self {
+        Self {
+            r1: commitment.encode(group),
+            r2: Vec::new(),
+        }
+    }
+}
+
+impl<G: Group> TryFrom<(&G, proto::Response)> for Response<G> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::Response)) -> Result<Self, DecodeError> {
+        Ok(Self {
+            s1: decode_scalar(group, "s", &message.s)?,
+            s2: decode_scalar(group, "s2", &message.s2)?,
+        })
+    }
+}
+
+impl<G: Group> From<(&G, &Response<G>)> for proto::Response {
+    fn from((group, response): (&G, &Response<G>)) -> Self {
+        Self {
+            s: group.encode_scalar(&response.s1),
+            s2: group.encode_scalar(&response.s2),
+        }
+    }
+}
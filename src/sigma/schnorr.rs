@@ -0,0 +1,179 @@
+use rand_core::{CryptoRng, RngCore};
+
+use super::{transcript_challenge, ProtocolId, SigmaProtocol};
+use crate::group::Group;
+use crate::proof::{
+    decode_element, impl_common_traits, split, unexpected, Challenge, DecodeError, Response,
+};
+use crate::proto;
+
+/// Schnorr's proof of knowledge of `x` with `y = G ^ x`
+///
+/// Half the size of a Chaum-Pedersen proof: one element each for the statement and the
+/// commitment. The response is the same `s = k - c · x`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schnorr;
+
+/// The public statement `y = G ^ x`
+pub struct Statement<G: Group> {
+    pub y: G::Element,
+}
+
+/// The prover's first message `r = G ^ k`
+pub struct Commitment<G: Group> {
+    pub r: G::Element,
+}
+
+impl_common_traits! {
+    Statement { y },
+    Commitment { r },
+}
+
+impl<G: Group> SigmaProtocol<G> for Schnorr {
+    const ID: ProtocolId = ProtocolId::Schnorr;
+
+    type Witness = G::Scalar;
+    type Statement = Statement<G>;
+    type Commitment = Commitment<G>;
+    type Response = Response<G>;
+
+    fn scalars(x: &G::Scalar) -> &[G::Scalar] {
+        std::slice::from_ref(x)
+    }
+
+    // x is a secret or a nonce, so this takes the constant-time path
+    fn statement(group: &G, x: &G::Scalar) -> Statement<G> {
+        Statement {
+            y: group.exp_secret(group.generators().0, x),
+        }
+    }
+
+    fn random_nonce<R: RngCore + CryptoRng>(group: &G, rng: &mut R) -> G::Scalar {
+        group.random_scalar(rng)
+    }
+
+    fn commit(group: &G, k: &G::Scalar) -> Commitment<G> {
+        Commitment {
+            r: group.exp_secret(group.generators().0, k),
+        }
+    }
+
+    // s = (k - (c * x)) mod q
+    fn respond(group: &G, k: &G::Scalar, challenge: &Challenge<G>, x: &G::Scalar) -> Response<G> {
+        Response::new(group, k, challenge, x)
+    }
+
+    // r ⇔ v = (G ^ s) · (y ^ c)
+    fn verify(
+        group: &G,
+        statement: &Statement<G>,
+        commitment: &Commitment<G>,
+        challenge: &Challenge<G>,
+        response: &Response<G>,
+    ) -> bool {
+        let (c, s) = (&challenge.c, &response.s);
+        if !group.contains_scalar(c) || !group.contains_scalar(s) {
+            return false;
+        }
+
+        commitment.r == group.multi_exp((group.generators().0, s), (&statement.y, c))
+    }
+
+    // c = SHA-512(group, "schnorr", y, r, user, context) mod q
+    fn derive_challenge(
+        group: &G,
+        statement: &Statement<G>,
+        commitment: &Commitment<G>,
+        user: &str,
+        context: &[u8],
+    ) -> Challenge<G> {
+        Challenge {
+            c: transcript_challenge(
+                group,
+                Some(ProtocolId::Schnorr.as_str()),
+                &[&statement.y, &commitment.r],
+                user,
+                context,
+            ),
+        }
+    }
+}
+
+impl<G: Group> Statement<G> {
+    /// The statement for the secret `x`
+    pub fn new(group: &G, x: &G::Scalar) -> Self {
+        Schnorr::statement(group, x)
+    }
+
+    /// `y`
+    pub fn encode(&self, group: &G) -> Vec<u8> {
+        group.encode_element(&self.y)
+    }
+
+    pub fn decode(group: &G, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let [y] = split(bytes, [group.element_len()])?;
+
+        Ok(Self {
+            y: decode_element(group, "y", y)?,
+        })
+    }
+}
+
+impl<G: Group> Commitment<G> {
+    /// `r`
+    pub fn encode(&self, group: &G) -> Vec<u8> {
+        group.encode_element(&self.r)
+    }
+
+    pub fn decode(group: &G, bytes: &[u8]) -> Result<Self, DecodeError> {
+        let [r] = split(bytes, [group.element_len()])?;
+
+        Ok(Self {
+            r: decode_element(group, "r", r)?,
+        })
+    }
+}
+
+// On the wire `y` and `r` travel as `y1` and `r1`, exactly the Chaum-Pedersen fields for `G`
+
+impl<G: Group> TryFrom<(&G, proto::Statement)> for Statement<G> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::Statement)) -> Result<Self, DecodeError> {
+        unexpected("y2", &message.y2)?;
+
+        Ok(Self {
+            y: decode_element(group, "y1", &message.y1)?,
+        })
+    }
+}
+
+impl<G: Group> From<(&G, &Statement<G>)> for proto::Statement {
+    fn from((group, statement): (&G, &Statement<G>)) -> Self {
+        Self {
+            y1: statement.encode(group),
+            y2: Vec::new(),
+        }
+    }
+}
+
+impl<G: Group> TryFrom<(&G, proto::Commitment)> for Commitment<G> {
+    type Error = DecodeError;
+
+    fn try_from((group, message): (&G, proto::Commitment)) -> Result<Self, DecodeError> {
+        unexpected("r2", &message.r2)?;
+
+        Ok(Self {
+            r: decode_element(group, "r1", &message.r1)?,
+        })
+    }
+}
+
+impl<G: Group> From<(&G, &Commitment<G>)> for proto::Commitment {
+    fn from((group, commitment): (&G, &Commitment<G>)) -> Self {
+        Self {
+            r1: commitment.encode(group),
+            r2: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::fmt;
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use crate::group::expand;
+use crate::Parameters;
+
+// Miller-Rabin rounds, each with a base derived from the candidate itself.
+// An adversary choosing parameters would need a composite that fools all of them: < 4^-40
+const MILLER_RABIN_ROUNDS: u32 = 40;
+
+const SMALL_PRIMES: [u8; 25] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParameterError {
+    /// `P` failed the primality test
+    CompositeModulus,
+    /// `Q` failed the primality test
+    CompositeOrder,
+    /// `P` is not the safe prime `2Q + 1`
+    NotSafePrime,
+    /// The named generator is trivial or does not generate the order-`Q` subgroup
+    InvalidGenerator(&'static str),
+    /// `G` and `H` are the same element
+    DependentGenerators,
+}
+
+impl fmt::Display for ParameterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParameterError::CompositeModulus => f.write_str("modulus 'P' is not prime"),
+            ParameterError::CompositeOrder => f.write_str("subgroup order 'Q' is not prime"),
+            ParameterError::NotSafePrime => f.write_str("'P' is not the safe prime 2Q + 1"),
+            ParameterError::InvalidGenerator(name) => {
+                write!(f, "'{}' does not generate the subgroup of order 'Q'", name)
+            }
+            ParameterError::DependentGenerators => f.write_str("'G' and 'H' must be distinct"),
+        }
+    }
+}
+
+impl std::error::Error for ParameterError {}
+
+impl Parameters {
+    /// Build a parameter set, rejecting anything that fails [`Parameters::validate`]
+    #[allow(non_snake_case)]
+    pub fn new(G: BigUint, P: BigUint, Q: BigUint, H: BigUint) -> Result<Self, ParameterError> {
+        let params = Parameters {
+            G,
+            P,
+            Q,
+            H,
+            name: "modp",
+        };
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Check that `P = 2Q + 1` with both prime, and that `G` and `H` are
+    /// distinct generators of the order-`Q` subgroup of Z_P*
+    pub fn validate(&self) -> Result<(), ParameterError> {
+        if !is_probable_prime(&self.Q) {
+            return Err(ParameterError::CompositeOrder);
+        }
+        if self.P != &self.Q * 2_u8 + 1_u8 {
+            return Err(ParameterError::NotSafePrime);
+        }
+        if !is_probable_prime(&self.P) {
+            return Err(ParameterError::CompositeModulus);
+        }
+
+        for (name, generator) in [("G", &self.G), ("H", &self.H)] {
+            // Q is prime, so any element other than 1 whose order divides Q has order exactly Q
+            if generator <= &BigUint::one()
+                || generator >= &self.P
+                || !generator.modpow(&self.Q, &self.P).is_one()
+            {
+                return Err(ParameterError::InvalidGenerator(name));
+            }
+        }
+        if self.G == self.H {
+            return Err(ParameterError::DependentGenerators);
+        }
+
+        Ok(())
+    }
+}
+
+/// Miller-Rabin, with bases hashed from `n` so the test is reproducible
+pub(crate) fn is_probable_prime(n: &BigUint) -> bool {
+    for p in SMALL_PRIMES {
+        let p = BigUint::from(p);
+        if n == &p {
+            return true;
+        }
+        if (n % &p).is_zero() {
+            return false;
+        }
+    }
+    if n < &BigUint::from(100_u8) {
+        return false;
+    }
+
+    let n_minus_one = n - 1_u8;
+    let s = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> s;
+    let two = BigUint::from(2_u8);
+    let len = n.bits().div_ceil(8) as usize + 16;
+
+    'witness: for round in 0..MILLER_RABIN_ROUNDS {
+        // a ∈ [2, n - 2]
+        let seed = [n.to_bytes_be(), round.to_be_bytes().to_vec()].concat();
+        let a = BigUint::from_bytes_be(&expand(&seed, len)) % (n - 3_u8) + 2_u8;
+
+        let mut x = a.modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+
+    true
+}
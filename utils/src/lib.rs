@@ -25,21 +25,105 @@ pub mod logger {
 }
 
 pub mod random {
+    use std::fmt;
+
     use num_bigint::{BigUint, RandBigInt};
     use num_traits::One;
-    use rand::Rng;
+    use rand::{rngs::OsRng, Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+    use rand_core::{CryptoRng, CryptoRngCore, RngCore};
+
+    /// A cryptographically secure source of randomness
+    ///
+    /// Defaults to the operating system's generator. A seeded one replays the exact same
+    /// stream every time, which makes whole protocol runs reproducible in tests.
+    pub struct SecureRng(Box<dyn CryptoRngCore + Send>);
+
+    impl SecureRng {
+        /// Draws from the operating system on every call
+        pub fn os() -> Self {
+            Self(Box::new(OsRng))
+        }
+
+        /// A deterministic ChaCha20 stream derived from `seed`
+        ///
+        /// Anyone who knows the seed can recompute every secret drawn from it, so this is for tests only.
+        pub fn seeded(seed: u64) -> Self {
+            Self(Box::new(ChaCha20Rng::seed_from_u64(seed)))
+        }
+
+        /// Wraps any other cryptographically secure generator
+        pub fn from_rng<R: CryptoRngCore + Send + 'static>(rng: R) -> Self {
+            Self(Box::new(rng))
+        }
+
+        pub fn alphanumeric(&mut self, n: usize) -> String {
+            self.sample_iter(rand::distributions::Alphanumeric)
+                .take(n)
+                .map(char::from)
+                .collect()
+        }
+
+        pub fn bytes(&mut self, n: usize) -> Vec<u8> {
+            let mut buf = vec![0; n];
+            self.fill(&mut buf[..]);
+            buf
+        }
+
+        /// Generate a random number in the range [1, n)
+        pub fn biguint(&mut self, ubound: &BigUint) -> BigUint {
+            self.gen_biguint_range(&BigUint::one(), ubound)
+        }
+    }
+
+    impl Default for SecureRng {
+        fn default() -> Self {
+            Self::os()
+        }
+    }
+
+    impl fmt::Debug for SecureRng {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("SecureRng")
+        }
+    }
+
+    impl RngCore for SecureRng {
+        fn next_u32(&mut self) -> u32 {
+            self.0.next_u32()
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0.next_u64()
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            self.0.fill_bytes(dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.0.try_fill_bytes(dest)
+        }
+    }
+
+    impl CryptoRng for SecureRng {}
+
+    /// The default, OS-backed generator
+    pub fn rng() -> SecureRng {
+        SecureRng::os()
+    }
 
     pub fn alphanumeric(n: usize) -> String {
-        rand::thread_rng()
-            .sample_iter(rand::distributions::Alphanumeric)
-            .take(n)
-            .map(char::from)
-            .collect()
+        rng().alphanumeric(n)
+    }
+
+    pub fn bytes(n: usize) -> Vec<u8> {
+        rng().bytes(n)
     }
 
     /// Generate a random number in the range [1, n)
     pub fn biguint(ubound: &BigUint) -> BigUint {
-        rand::thread_rng().gen_biguint_range(&BigUint::one(), ubound)
+        rng().biguint(ubound)
     }
 }
 